@@ -0,0 +1,382 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use anyhow::{anyhow, Context};
+use redox_core::util::write_private_file;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::error::RedoxApiError;
+
+/// Environment variable naming a cassette to record every request to
+pub const RECORD_ENV: &str = "RC_RECORD";
+/// Environment variable naming a cassette to replay requests from
+pub const REPLAY_ENV: &str = "RC_REPLAY";
+
+/// Stands in for any redacted value
+const REDACTED: &str = "[REDACTED]";
+
+/// Fields whose values never make it into a cassette, matched case
+/// insensitively at any depth. Covers secrets in resource bodies and the
+/// patient data in log payloads.
+pub const DEFAULT_REDACTED_FIELDS: &[&str] = &[
+    // Secrets
+    "access_token",
+    "authorization",
+    "clientSecret",
+    "password",
+    "privateKey",
+    "token",
+    "verificationToken",
+    // PHI
+    "Address",
+    "DOB",
+    "Demographics",
+    "EmailAddresses",
+    "FirstName",
+    "Identifiers",
+    "LastName",
+    "MiddleName",
+    "PhoneNumber",
+    "SSN",
+];
+
+/// Whether a cassette is being written or read back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests as usual and save each one with its response
+    Record,
+    /// Answer requests from the saved responses without touching the network
+    Replay,
+}
+
+/// A file of request/response pairs captured from a real deployment, so a
+/// bug can be reproduced later without it. Recording never stores headers,
+/// so bearer tokens are left out, and redacts the values of sensitive
+/// fields in bodies.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    redacted_fields: Vec<String>,
+    state: Arc<Mutex<CassetteState>>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Which interactions have already been replayed
+    used: Vec<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct RecordedRequest {
+    method: String,
+    /// Relative to the API host, so a cassette can be replayed against any
+    /// base URL
+    path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    query: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    /// The body as JSON when it parses, otherwise as a string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+impl Cassette {
+    /// Record to the given file, replacing anything already in it
+    pub fn record(path: PathBuf) -> Self {
+        Self::new(CassetteMode::Record, path, CassetteState::default())
+    }
+
+    /// Replay the interactions in the given file
+    pub fn replay(path: PathBuf) -> Result<Self, RedoxApiError> {
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("Error reading cassette {path:?}"))
+            .map_err(RedoxApiError::Cassette)?;
+        let file: CassetteFile = serde_json::from_str(&data)
+            .with_context(|| format!("Error parsing cassette {path:?}"))
+            .map_err(RedoxApiError::Cassette)?;
+        debug!(
+            "loaded {} interactions from {:?}",
+            file.interactions.len(),
+            path
+        );
+
+        let state = CassetteState {
+            used: vec![false; file.interactions.len()],
+            interactions: file.interactions,
+        };
+        Ok(Self::new(CassetteMode::Replay, path, state))
+    }
+
+    /// A cassette named by [RECORD_ENV] or [REPLAY_ENV], if either is set.
+    /// It's loaded once and shared by every client in the process, so a run
+    /// that builds several clients records all of their requests to the one
+    /// file.
+    pub fn from_env() -> Result<Option<Self>, RedoxApiError> {
+        static SHARED: OnceLock<Option<Cassette>> = OnceLock::new();
+        if let Some(cassette) = SHARED.get() {
+            return Ok(cassette.clone());
+        }
+
+        let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+        let cassette = match (var(RECORD_ENV), var(REPLAY_ENV)) {
+            (Some(_), Some(_)) => {
+                return Err(RedoxApiError::Cassette(anyhow!(
+                    "Only one of ${RECORD_ENV} and ${REPLAY_ENV} may be set"
+                )))
+            }
+            (Some(path), None) => Some(Self::record(path.into())),
+            (None, Some(path)) => Some(Self::replay(path.into())?),
+            (None, None) => None,
+        };
+        Ok(SHARED.get_or_init(|| cassette).clone())
+    }
+
+    fn new(mode: CassetteMode, path: PathBuf, state: CassetteState) -> Self {
+        Self {
+            mode,
+            path,
+            redacted_fields: DEFAULT_REDACTED_FIELDS
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Redact these fields as well as the defaults
+    pub fn with_redacted_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redacted_fields
+            .extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Save a request and the response it got. The cassette is rewritten
+    /// after every request, so it's complete even if the tool is killed.
+    pub(crate) fn record_interaction(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<Value>,
+        status: StatusCode,
+        text: &str,
+    ) {
+        let response_body = if text.trim().is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string())))
+        };
+        let interaction = Interaction {
+            request: self.request(method, path, query, body),
+            response: RecordedResponse {
+                status: status.as_u16(),
+                body: response_body.map(|b| self.redact(b)),
+            },
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction);
+        state.used.push(true);
+        if let Err(e) = self.save(&state.interactions) {
+            warn!("failed to write cassette {:?}. Error: {:#}", self.path, e);
+        }
+    }
+
+    /// Find the response to a request. Identical requests are answered by
+    /// their recordings in order, and once those run out the last one is
+    /// repeated.
+    pub(crate) fn replay_interaction(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<Value>,
+    ) -> Result<(StatusCode, String), RedoxApiError> {
+        let request = self.request(method, path, query, body);
+        let mut state = self.state.lock().unwrap();
+
+        let matching: Vec<usize> = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request == request)
+            .map(|(i, _)| i)
+            .collect();
+        let index = matching
+            .iter()
+            .find(|i| !state.used[**i])
+            .or(matching.last())
+            .copied()
+            .ok_or_else(|| {
+                RedoxApiError::Cassette(anyhow!(
+                    "No recording of {} {} in cassette {:?}",
+                    method,
+                    path,
+                    self.path
+                ))
+            })?;
+        state.used[index] = true;
+
+        let response = &state.interactions[index].response;
+        let status = StatusCode::from_u16(response.status)
+            .map_err(|e| RedoxApiError::Cassette(anyhow!(e)))?;
+        let text = match &response.body {
+            None => String::new(),
+            Some(Value::String(text)) => text.clone(),
+            Some(body) => body.to_string(),
+        };
+        debug!("replayed {} {} from cassette", method, path);
+        Ok((status, text))
+    }
+
+    fn request(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<Value>,
+    ) -> RecordedRequest {
+        RecordedRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.to_vec(),
+            body: body.map(|b| self.redact(b)),
+        }
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = CassetteFile {
+            interactions: interactions.to_vec(),
+        };
+        // Redaction can't know every sensitive field, so keep the file
+        // private in case something slips through
+        write_private_file(&self.path, &serde_json::to_string_pretty(&file)?, true)
+    }
+
+    /// Replace the values of redacted fields, wherever they are
+    fn redact(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(name, value)| {
+                        if self
+                            .redacted_fields
+                            .iter()
+                            .any(|f| f.eq_ignore_ascii_case(&name))
+                        {
+                            (name, Value::String(REDACTED.to_string()))
+                        } else {
+                            (name, self.redact(value))
+                        }
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.redact(v)).collect())
+            }
+            value => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_nested_phi_and_secrets() {
+        let cassette = Cassette::record(PathBuf::from("unused.json"));
+        let body = json!({
+            "payload": {
+                "name": "EHR",
+                "verificationToken": "secret",
+                "auth": { "type": "oauth", "clientSecret": "secret" },
+                "logs": [{
+                    "id": "log-1",
+                    "payload": {
+                        "Patient": {
+                            "Identifiers": [{ "ID": "123" }],
+                            "Demographics": { "FirstName": "Jo" },
+                            "Contact": { "firstname": "Jo", "Password": "secret" },
+                        },
+                    },
+                }],
+            },
+        });
+
+        assert_eq!(
+            cassette.redact(body),
+            json!({
+                "payload": {
+                    "name": "EHR",
+                    "verificationToken": REDACTED,
+                    "auth": { "type": "oauth", "clientSecret": REDACTED },
+                    "logs": [{
+                        "id": "log-1",
+                        "payload": {
+                            "Patient": {
+                                "Identifiers": REDACTED,
+                                "Demographics": REDACTED,
+                                "Contact": { "firstname": REDACTED, "Password": REDACTED },
+                            },
+                        },
+                    }],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn redacts_extra_fields() {
+        let cassette = Cassette::record(PathBuf::from("unused.json")).with_redacted_fields(["MRN"]);
+        assert_eq!(
+            cassette.redact(json!({ "mrn": "123", "name": "EHR" })),
+            json!({ "mrn": REDACTED, "name": "EHR" })
+        );
+    }
+}
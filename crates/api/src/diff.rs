@@ -0,0 +1,516 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use strum::{Display, IntoEnumIterator};
+
+use crate::{
+    models::{
+        alert::AlertResource,
+        auth_credential::AuthCredentialResource,
+        config_modifier::ConfigModifierResource,
+        destination::DestinationResource,
+        filter::FilterResource,
+        log::{LogFilter, LogResource},
+        source::SourceResource,
+        EnvironmentResources,
+    },
+    snapshot::{list, translation_sets},
+    RedoxApiError, RedoxRequestClient,
+};
+
+/// How far back logs are fetched for a comparison. Logs pile up quickly and
+/// rarely line up between environments, so only recent ones are worth it.
+const LOG_WINDOW: Duration = Duration::days(1);
+
+/// What's compared unless asked for something else: everything that's
+/// configured. Logs and alerts are left out because they're matched by ID,
+/// which never matches between environments, so every recent one would show up
+/// as added or removed and bury the configuration that actually differs.
+pub const DEFAULT_KINDS: &[EnvironmentResources] = &[
+    EnvironmentResources::Sources,
+    EnvironmentResources::Destination,
+    EnvironmentResources::Filters,
+    EnvironmentResources::ConfigModifiers,
+    EnvironmentResources::TranslationSets,
+    EnvironmentResources::AuthCredential,
+];
+
+/// Fields that always differ between environments and say nothing about how
+/// they're configured
+const IGNORED_FIELDS: &[&str] = &["id", "createdAt", "updatedAt"];
+
+/// Fields holding credentials, such as endpoint verification tokens and
+/// `auth.password`, matched case insensitively at any depth. They're still
+/// compared, but their values are masked so a diff never shows them.
+const SECRET_FIELDS: &[&str] = &[
+    "clientSecret",
+    "password",
+    "privateKey",
+    "token",
+    "verificationToken",
+];
+
+/// Shown in place of a secret's value
+const MASKED: &str = "[REDACTED]";
+
+/// Every item in an environment for the resource types that were fetched, as
+/// the JSON the API returned
+#[derive(Debug, Clone, Default)]
+pub struct EnvironmentItems {
+    items: HashMap<EnvironmentResources, Vec<Value>>,
+    /// Source and destination names by ID, for describing what filters and
+    /// config modifiers are attached to without the environment specific IDs
+    endpoint_names: HashMap<String, String>,
+}
+
+impl EnvironmentItems {
+    /// Fetch the items of the given resource types from an environment. Only
+    /// the last day of logs is fetched.
+    pub async fn fetch(
+        client: &RedoxRequestClient,
+        org_id: i32,
+        environment_id: &str,
+        kinds: &[EnvironmentResources],
+    ) -> Result<Self, RedoxApiError> {
+        let mut items = HashMap::new();
+        for kind in kinds {
+            items.insert(
+                *kind,
+                fetch_kind(client, org_id, environment_id, *kind).await?,
+            );
+        }
+
+        let mut endpoint_names = HashMap::new();
+        let attachable = [
+            EnvironmentResources::Filters,
+            EnvironmentResources::ConfigModifiers,
+        ];
+        if kinds.iter().any(|kind| attachable.contains(kind)) {
+            for kind in [
+                EnvironmentResources::Sources,
+                EnvironmentResources::Destination,
+            ] {
+                let endpoints = match items.get(&kind) {
+                    Some(endpoints) => endpoints.clone(),
+                    None => fetch_kind(client, org_id, environment_id, kind).await?,
+                };
+                endpoint_names.extend(
+                    endpoints
+                        .iter()
+                        .filter_map(|item| Some((text(item, "id")?, text(item, "name")?))),
+                );
+            }
+        }
+        Ok(Self {
+            items,
+            endpoint_names,
+        })
+    }
+
+    /// The fetched items of one resource type
+    pub fn get(&self, kind: EnvironmentResources) -> &[Value] {
+        self.items.get(&kind).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Every item of one resource type, as JSON
+async fn fetch_kind(
+    client: &RedoxRequestClient,
+    org_id: i32,
+    environment_id: &str,
+    kind: EnvironmentResources,
+) -> Result<Vec<Value>, RedoxApiError> {
+    Ok(match kind {
+        EnvironmentResources::Alerts => to_values(
+            list(client, AlertResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.alerts),
+        ),
+        EnvironmentResources::AuthCredential => to_values(
+            list(client, AuthCredentialResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.auth_credentials),
+        ),
+        EnvironmentResources::ConfigModifiers => to_values(
+            list(client, ConfigModifierResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.config_modifiers),
+        ),
+        EnvironmentResources::Destination => to_values(
+            list(client, DestinationResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.destinations),
+        ),
+        EnvironmentResources::Filters => to_values(
+            list(client, FilterResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.filters),
+        ),
+        EnvironmentResources::Logs => {
+            let filter = LogFilter {
+                start: Some(Utc::now() - LOG_WINDOW),
+                ..Default::default()
+            };
+            let resource = LogResource::new(org_id, environment_id).with_filter(filter);
+            to_values(list(client, resource).await?.map(|l| l.logs))
+        }
+        EnvironmentResources::Sources => to_values(
+            list(client, SourceResource::new(org_id, environment_id))
+                .await?
+                .map(|l| l.sources),
+        ),
+        EnvironmentResources::TranslationSets => to_values(Some(
+            translation_sets(client, org_id, environment_id).await?,
+        )),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Change {
+    /// Only in the right environment
+    Added,
+    /// Only in the left environment
+    Removed,
+    Changed,
+}
+
+/// A field that differs between the left and right versions of an item. A
+/// side is `None` when it doesn't have the field at all.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    /// Where the field is in the item, e.g. `subscriptions[0].dataModel`
+    pub path: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+/// An item that isn't the same in both environments. Items are matched by
+/// name, or by ID if they don't have one.
+#[derive(Debug, Clone)]
+pub struct ItemDiff {
+    pub kind: EnvironmentResources,
+    pub name: String,
+    pub change: Change,
+    /// Only set for changed items
+    pub fields: Vec<FieldDiff>,
+    /// The item as compared, without the fields that always differ
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+/// Compare two environments, returning every item that was added, removed or
+/// changed going from left to right. Resource types that weren't fetched for
+/// both sides are skipped.
+pub fn diff(left: &EnvironmentItems, right: &EnvironmentItems) -> Vec<ItemDiff> {
+    let mut diffs = Vec::new();
+    for kind in EnvironmentResources::iter() {
+        if !left.items.contains_key(&kind) || !right.items.contains_key(&kind) {
+            continue;
+        }
+        let left_items = keyed(left.get(kind), &left.endpoint_names);
+        let mut right_items = keyed(right.get(kind), &right.endpoint_names);
+
+        for (name, left_item) in left_items {
+            match right_items.remove(&name) {
+                Some(right_item) => {
+                    let fields = diff_fields(&left_item, &right_item);
+                    if !fields.is_empty() {
+                        diffs.push(ItemDiff {
+                            kind,
+                            name,
+                            change: Change::Changed,
+                            fields,
+                            left: Some(masked(left_item)),
+                            right: Some(masked(right_item)),
+                        });
+                    }
+                }
+                None => diffs.push(ItemDiff {
+                    kind,
+                    name,
+                    change: Change::Removed,
+                    fields: Vec::new(),
+                    left: Some(masked(left_item)),
+                    right: None,
+                }),
+            }
+        }
+        diffs.extend(right_items.into_iter().map(|(name, right_item)| ItemDiff {
+            kind,
+            name,
+            change: Change::Added,
+            fields: Vec::new(),
+            left: None,
+            right: Some(masked(right_item)),
+        }));
+    }
+    diffs
+}
+
+/// Every leaf of a JSON value by its path. Arrays of plain values count as a
+/// single leaf, since they read better that way than element by element.
+pub fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    let mut leaves = BTreeMap::new();
+    flatten_into("", value, &mut leaves);
+    leaves
+}
+
+fn flatten_into(path: &str, value: &Value, leaves: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (name, value) in map {
+                let path = match path {
+                    "" => name.clone(),
+                    path => format!("{path}.{name}"),
+                };
+                flatten_into(&path, value, leaves);
+            }
+        }
+        Value::Array(items) if items.iter().any(|i| i.is_object() || i.is_array()) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten_into(&format!("{path}[{index}]"), item, leaves);
+            }
+        }
+        value => {
+            leaves.insert(path.to_string(), value.clone());
+        }
+    }
+}
+
+/// The fields that differ between two items, with secrets masked
+pub(crate) fn diff_fields(left: &Value, right: &Value) -> Vec<FieldDiff> {
+    let left = flatten(left);
+    let mut right = flatten(right);
+
+    let mut fields = Vec::new();
+    for (path, left_value) in left {
+        match right.remove(&path) {
+            Some(right_value) if right_value == left_value => {}
+            right_value => fields.push(FieldDiff {
+                path,
+                left: Some(left_value),
+                right: right_value,
+            }),
+        }
+    }
+    fields.extend(right.into_iter().map(|(path, right_value)| FieldDiff {
+        path,
+        left: None,
+        right: Some(right_value),
+    }));
+    fields.sort_by(|a, b| a.path.cmp(&b.path));
+    for field in &mut fields {
+        let leaf = field.path.rsplit('.').next().unwrap_or_default();
+        let name = leaf.split('[').next().unwrap_or_default();
+        if is_secret(name) {
+            field.left = field.left.take().map(mask_secret);
+            field.right = field.right.take().map(mask_secret);
+        }
+    }
+    fields
+}
+
+fn is_secret(name: &str) -> bool {
+    SECRET_FIELDS
+        .iter()
+        .any(|secret| secret.eq_ignore_ascii_case(name))
+}
+
+/// Replace the values of secret fields, wherever they are. Nulls are kept, so
+/// it's still clear when a secret isn't set.
+fn masked(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(name, value)| {
+                    let value = if is_secret(&name) {
+                        mask_secret(value)
+                    } else {
+                        masked(value)
+                    };
+                    (name, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(masked).collect()),
+        value => value,
+    }
+}
+
+fn mask_secret(value: Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        _ => Value::String(MASKED.to_string()),
+    }
+}
+
+/// Normalize items and key them by name. Repeated names get a number, so
+/// nothing is lost.
+fn keyed(items: &[Value], endpoint_names: &HashMap<String, String>) -> BTreeMap<String, Value> {
+    let mut keyed = BTreeMap::new();
+    for item in items {
+        let base = text(item, "name")
+            .or_else(|| text(item, "id"))
+            .unwrap_or_default();
+        let mut key = base.clone();
+        let mut count = 1;
+        while keyed.contains_key(&key) {
+            count += 1;
+            key = format!("{base} ({count})");
+        }
+        keyed.insert(key, normalize(item, endpoint_names));
+    }
+    keyed
+}
+
+/// Drop the fields that always differ, and swap attached endpoint IDs for
+/// their names
+pub(crate) fn normalize(item: &Value, endpoint_names: &HashMap<String, String>) -> Value {
+    let Value::Object(map) = item else {
+        return item.clone();
+    };
+    let mut map: Map<String, Value> = map
+        .iter()
+        .filter(|(name, _)| !IGNORED_FIELDS.contains(&name.as_str()))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+
+    if let Some(Value::Object(attached)) = map.get_mut("attachedTo") {
+        for ids in attached.values_mut() {
+            if let Value::Array(ids) = ids {
+                for id in ids.iter_mut() {
+                    if let Some(name) = id.as_str().and_then(|id| endpoint_names.get(id)) {
+                        *id = Value::String(name.clone());
+                    }
+                }
+            }
+        }
+    }
+    Value::Object(map)
+}
+
+fn text(item: &Value, field: &str) -> Option<String> {
+    match item.get(field)? {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn to_values<T: Serialize>(items: Option<Vec<T>>) -> Vec<Value> {
+    items
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filters(filters: Vec<Value>) -> EnvironmentItems {
+        EnvironmentItems {
+            items: HashMap::from([(EnvironmentResources::Filters, filters)]),
+            endpoint_names: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn finds_added_removed_and_changed_items() {
+        let left = filters(vec![
+            json!({ "id": "l-1", "name": "Same", "type": "allow" }),
+            json!({ "id": "l-2", "name": "Edited", "type": "allow", "dataModel": "Scheduling" }),
+            json!({ "id": "l-3", "name": "Only left", "type": "allow" }),
+        ]);
+        let right = filters(vec![
+            json!({ "id": "r-1", "name": "Same", "type": "allow" }),
+            json!({ "id": "r-2", "name": "Edited", "type": "deny" }),
+            json!({ "id": "r-4", "name": "Only right", "type": "allow" }),
+        ]);
+
+        let mut diffs = diff(&left, &right);
+        diffs.sort_by(|a, b| a.name.cmp(&b.name));
+        let changes: Vec<_> = diffs.iter().map(|d| (d.name.as_str(), d.change)).collect();
+        assert_eq!(
+            changes,
+            [
+                ("Edited", Change::Changed),
+                ("Only left", Change::Removed),
+                ("Only right", Change::Added),
+            ]
+        );
+
+        // IDs always differ, so they're never reported
+        let fields: Vec<_> = diffs[0]
+            .fields
+            .iter()
+            .map(|f| (f.path.as_str(), f.left.clone(), f.right.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("dataModel", Some(json!("Scheduling")), None),
+                ("type", Some(json!("allow")), Some(json!("deny"))),
+            ]
+        );
+        assert!(diffs[1].right.is_none() && diffs[2].left.is_none());
+    }
+
+    #[test]
+    fn same_items_have_no_differences() {
+        let items = filters(vec![
+            json!({ "id": "f-1", "name": "Same", "type": "allow" }),
+        ]);
+        let mut other = items.clone();
+        other.items.get_mut(&EnvironmentResources::Filters).unwrap()[0]["id"] = json!("f-2");
+        assert!(diff(&items, &other).is_empty());
+    }
+
+    #[test]
+    fn masks_secrets_that_differ() {
+        let items = |token: &str, password: &str| EnvironmentItems {
+            items: HashMap::from([(
+                EnvironmentResources::Destination,
+                vec![json!({
+                    "id": format!("dst-{token}"),
+                    "name": "Patient app",
+                    "verificationToken": token,
+                    "auth": { "type": "basic", "password": password, "clientSecret": null },
+                })],
+            )]),
+            endpoint_names: HashMap::new(),
+        };
+
+        let diffs = diff(&items("left-token", "pw"), &items("right-token", "pw"));
+        let [item] = diffs.as_slice() else {
+            panic!("expected one changed item, got {diffs:?}");
+        };
+        let fields: Vec<_> = item
+            .fields
+            .iter()
+            .map(|f| (f.path.as_str(), f.left.clone(), f.right.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            [(
+                "verificationToken",
+                Some(json!(MASKED)),
+                Some(json!(MASKED))
+            )]
+        );
+        assert_eq!(
+            item.left,
+            Some(json!({
+                "name": "Patient app",
+                "verificationToken": MASKED,
+                "auth": { "type": "basic", "password": MASKED, "clientSecret": null },
+            }))
+        );
+    }
+}
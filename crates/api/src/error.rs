@@ -0,0 +1,164 @@
+use std::fmt::{self, Display, Formatter};
+
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Everything that can go wrong while talking to the Redox API. Callers can
+/// match on the variant (or use the helpers below) to treat a rejected
+/// credential differently from a missing permission or a server outage.
+#[derive(Debug, Error)]
+pub enum RedoxApiError {
+    /// The private key couldn't be loaded, or the client assertion couldn't
+    /// be signed with it
+    #[error("Private key error: {0:#}")]
+    Key(anyhow::Error),
+
+    /// The auth host refused to exchange our client assertion for a token
+    #[error("Failed to retrieve JWT. Status code: {status}. {}", describe(.error, .raw))]
+    Auth {
+        status: StatusCode,
+        error: Option<Box<RedoxErrorBody>>,
+        raw: String,
+    },
+
+    /// The API responded with a non-success status
+    #[error("{method} {url} failed. Status code: {status}. {}", describe(.error, .raw))]
+    Http {
+        method: Method,
+        url: String,
+        status: StatusCode,
+        error: Option<Box<RedoxErrorBody>>,
+        raw: String,
+    },
+
+    /// The response body didn't match the shape we expected. The raw payload
+    /// is kept around so it can be logged or reported.
+    #[error("Failed to parse response: {source}")]
+    Deserialize {
+        source: serde_json::Error,
+        payload: String,
+    },
+
+    /// The request never got a response, e.g. DNS, TLS or connection failures
+    #[error("Network failure: {0}")]
+    Network(#[from] reqwest::Error),
+
+    /// A cassette couldn't be read, or has no recording of a request
+    #[error("Cassette error: {0:#}")]
+    Cassette(anyhow::Error),
+
+    /// Promoted items are attached to sources or destinations that the
+    /// target environment has nothing of the same name for
+    #[error("The target environment has no sources or destinations named {}", .0.join(", "))]
+    MissingEndpoints(Vec<String>),
+
+    /// The resource doesn't offer this kind of request
+    #[error("The {request} request is not supported for {resource}")]
+    Unsupported {
+        request: &'static str,
+        resource: String,
+    },
+}
+
+impl RedoxApiError {
+    /// Build an error from a payload that failed to deserialize
+    pub(crate) fn deserialize(source: serde_json::Error, payload: impl ToString) -> Self {
+        Self::Deserialize {
+            source,
+            payload: payload.to_string(),
+        }
+    }
+
+    /// The HTTP status that caused this error, if there was one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Auth { status, .. } | Self::Http { status, .. } => Some(*status),
+            Self::Network(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// The parsed Redox error body, if the API sent one
+    pub fn error_body(&self) -> Option<&RedoxErrorBody> {
+        match self {
+            Self::Auth { error, .. } | Self::Http { error, .. } => error.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Our credentials were rejected, either by the auth host or the API
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Self::Auth { .. }) || self.status() == Some(StatusCode::UNAUTHORIZED)
+    }
+
+    /// The credentials are valid but not allowed to do this
+    pub fn is_forbidden(&self) -> bool {
+        self.status() == Some(StatusCode::FORBIDDEN)
+    }
+
+    /// Redox itself had a problem; trying again later may help
+    pub fn is_server_error(&self) -> bool {
+        self.status().is_some_and(|s| s.is_server_error())
+    }
+}
+
+/// The body Redox sends along with a failed request. The API and the auth
+/// host don't agree on a shape, so every field is optional.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RedoxErrorBody {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<RedoxErrorDetail>,
+    /// OAuth style error code, sent by the auth host
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_description: Option<String>,
+}
+
+impl RedoxErrorBody {
+    /// Parse an error body, returning `None` if it isn't JSON we recognize
+    pub fn parse(raw: &str) -> Option<Box<Self>> {
+        serde_json::from_str(raw).ok().map(Box::new)
+    }
+}
+
+impl Display for RedoxErrorBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut messages = vec![];
+        messages.extend(self.message.clone());
+        messages.extend(self.error.clone());
+        messages.extend(self.error_description.clone());
+        messages.extend(self.errors.iter().map(|e| e.to_string()));
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RedoxErrorDetail {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default, alias = "text")]
+    pub message: Option<String>,
+}
+
+impl Display for RedoxErrorDetail {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.code, &self.message) {
+            (Some(code), Some(message)) => write!(f, "{code}: {message}"),
+            (None, Some(message)) => write!(f, "{message}"),
+            (Some(code), None) => write!(f, "{code}"),
+            (None, None) => write!(f, "unknown error"),
+        }
+    }
+}
+
+/// Describe an error response, preferring the parsed body over the raw one
+fn describe(error: &Option<Box<RedoxErrorBody>>, raw: &str) -> String {
+    match error {
+        Some(error) => error.to_string(),
+        None => raw.to_string(),
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::Algorithm;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use pkcs8::{der::pem::LineEnding, EncodePrivateKey};
+use rsa::{rand_core::OsRng, traits::PublicKeyParts, RsaPrivateKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::SUPPORTED_ALGORITHMS;
+
+/// RSA key size used when none is given
+pub const DEFAULT_RSA_BITS: usize = 2048;
+
+/// Smallest RSA key we'll generate, anything shorter is too weak to sign with
+pub const MIN_RSA_BITS: usize = 2048;
+
+/// A freshly generated key pair. The private half is PEM encoded, ready to be
+/// written to the file a deployment points at, and the public half is a JWK
+/// ready to be uploaded to Redox.
+pub struct GeneratedKey {
+    pub private_key_pem: String,
+    pub jwk: Jwk,
+}
+
+impl GeneratedKey {
+    /// The key ID, shared by the JWK and the deployment config
+    pub fn kid(&self) -> &str {
+        &self.jwk.kid
+    }
+}
+
+/// A public key in JSON Web Key form. Only the members for RSA and EC keys
+/// are modeled.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Jwk {
+    pub kty: String,
+    pub kid: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// A JSON Web Key Set, the document Redox expects for a public key
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwk {
+    fn new(algorithm: Algorithm, kty: &str) -> Self {
+        Self {
+            kty: kty.to_string(),
+            kid: String::new(),
+            alg: format!("{:?}", algorithm),
+            key_use: "sig".to_string(),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+
+    /// The RFC 7638 thumbprint of the key, which makes for a stable kid
+    fn thumbprint(&self) -> String {
+        // Only the required members, in lexicographic order, with no whitespace
+        let canonical = match self.kty.as_str() {
+            "EC" => serde_json::json!({
+                "crv": self.crv,
+                "kty": self.kty,
+                "x": self.x,
+                "y": self.y,
+            }),
+            _ => serde_json::json!({
+                "e": self.e,
+                "kty": self.kty,
+                "n": self.n,
+            }),
+        };
+        URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.to_string()))
+    }
+}
+
+/// Generate a key pair for the given algorithm. `rsa_bits` is ignored for EC
+/// algorithms. The kid is the key's thumbprint.
+pub fn generate_key(
+    algorithm: Algorithm,
+    rsa_bits: Option<usize>,
+) -> anyhow::Result<GeneratedKey, anyhow::Error> {
+    if !SUPPORTED_ALGORITHMS.contains(&algorithm) {
+        bail!(
+            "Unsupported signing algorithm {:?}. Expected one of {:?}",
+            algorithm,
+            SUPPORTED_ALGORITHMS
+        );
+    }
+
+    let (private_key_pem, mut jwk) = match algorithm {
+        Algorithm::ES256 => {
+            let secret = p256::SecretKey::random(&mut OsRng);
+            let point = secret.public_key().to_encoded_point(false);
+            let mut jwk = Jwk::new(algorithm, "EC");
+            jwk.crv = Some("P-256".to_string());
+            jwk.x = point.x().map(|x| URL_SAFE_NO_PAD.encode(x));
+            jwk.y = point.y().map(|y| URL_SAFE_NO_PAD.encode(y));
+            (secret.to_pkcs8_pem(LineEnding::LF), jwk)
+        }
+        Algorithm::ES384 => {
+            let secret = p384::SecretKey::random(&mut OsRng);
+            let point = secret.public_key().to_encoded_point(false);
+            let mut jwk = Jwk::new(algorithm, "EC");
+            jwk.crv = Some("P-384".to_string());
+            jwk.x = point.x().map(|x| URL_SAFE_NO_PAD.encode(x));
+            jwk.y = point.y().map(|y| URL_SAFE_NO_PAD.encode(y));
+            (secret.to_pkcs8_pem(LineEnding::LF), jwk)
+        }
+        _ => {
+            let bits = rsa_bits.unwrap_or(DEFAULT_RSA_BITS);
+            if bits < MIN_RSA_BITS {
+                bail!("RSA keys need at least {MIN_RSA_BITS} bits, {bits} is too few");
+            }
+            let private_key = RsaPrivateKey::new(&mut OsRng, bits)
+                .map_err(|e| anyhow!(format!("Failed to generate RSA key. Error: {:?}", e)))?;
+            let mut jwk = Jwk::new(algorithm, "RSA");
+            jwk.n = Some(URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be()));
+            jwk.e = Some(URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be()));
+            (private_key.to_pkcs8_pem(LineEnding::LF), jwk)
+        }
+    };
+    let private_key_pem = private_key_pem
+        .map_err(|e| anyhow!(format!("Failed to encode private key. Error: {:?}", e)))?
+        .to_string();
+
+    jwk.kid = jwk.thumbprint();
+    Ok(GeneratedKey {
+        private_key_pem,
+        jwk,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{decode, DecodingKey, Header, Validation};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::key::Key;
+
+    /// Sign with the private key and verify with the public JWK, the way
+    /// Redox will
+    fn assert_key_pair(key: &GeneratedKey, algorithm: Algorithm) {
+        let private_key = Key::from_pem(&key.private_key_pem, Some(&key.jwk.alg), None).unwrap();
+        assert_eq!(private_key.algorithm(), algorithm);
+
+        let claims = json!({ "sub": "client", "exp": 4102444800u64 });
+        let token = private_key
+            .generate_signed_jwt(&Header::new(algorithm), &claims)
+            .unwrap();
+        let jwk = &key.jwk;
+        let public_key = match jwk.kty.as_str() {
+            "EC" => DecodingKey::from_ec_components(
+                jwk.x.as_deref().unwrap(),
+                jwk.y.as_deref().unwrap(),
+            ),
+            _ => DecodingKey::from_rsa_components(
+                jwk.n.as_deref().unwrap(),
+                jwk.e.as_deref().unwrap(),
+            ),
+        }
+        .unwrap();
+        decode::<Value>(&token, &public_key, &Validation::new(algorithm)).unwrap();
+    }
+
+    #[test]
+    fn thumbprints_match_rfc_7638() {
+        // The example key from section 3.1 of the RFC
+        let mut jwk = Jwk::new(Algorithm::RS256, "RSA");
+        jwk.n = Some(
+            "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw"
+                .to_string(),
+        );
+        jwk.e = Some("AQAB".to_string());
+        assert_eq!(
+            jwk.thumbprint(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn generates_rsa_keys() {
+        let key = generate_key(Algorithm::RS384, None).unwrap();
+        assert_eq!(key.kid(), key.jwk.thumbprint());
+        assert_eq!(
+            (key.jwk.kty.as_str(), key.jwk.alg.as_str()),
+            ("RSA", "RS384")
+        );
+        assert_key_pair(&key, Algorithm::RS384);
+    }
+
+    #[test]
+    fn generates_ec_keys() {
+        for (algorithm, curve) in [(Algorithm::ES256, "P-256"), (Algorithm::ES384, "P-384")] {
+            let key = generate_key(algorithm, None).unwrap();
+            assert_eq!(key.kid(), key.jwk.thumbprint());
+            assert_eq!(key.jwk.crv.as_deref(), Some(curve));
+            assert_key_pair(&key, algorithm);
+        }
+    }
+
+    #[test]
+    fn jwks_parse_back() {
+        let keys = [Algorithm::ES256, Algorithm::ES384]
+            .map(|algorithm| generate_key(algorithm, None).unwrap().jwk)
+            .to_vec();
+        let jwks = Jwks { keys };
+
+        let written = serde_json::to_string_pretty(&jwks).unwrap();
+        assert_eq!(serde_json::from_str::<Jwks>(&written).unwrap(), jwks);
+        // EC keys don't have RSA members, and "use" is spelled the JWK way
+        let value: Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(value["keys"][0]["use"], "sig");
+        assert!(value["keys"][0].get("n").is_none());
+    }
+
+    #[test]
+    fn rejects_short_rsa_keys() {
+        let error = generate_key(Algorithm::RS256, Some(1024)).err().unwrap();
+        assert!(error.to_string().contains("at least 2048"));
+    }
+
+    #[test]
+    fn rejects_unsupported_algorithms() {
+        assert!(generate_key(Algorithm::HS256, None).is_err());
+    }
+}
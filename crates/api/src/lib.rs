@@ -1,22 +1,48 @@
 use core::fmt;
 use std::{
     fmt::{Debug, Formatter},
+    path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 
-use anyhow::anyhow;
+use anyhow::Context;
 use chrono::Utc;
-use jsonwebtoken::{Algorithm, Header};
+use jsonwebtoken::Header;
 use key::Key;
-use models::{auth::AuthToken, RedoxApiResource, RequestType};
-use reqwest::{Client, Method, StatusCode};
-use serde::{Deserialize, Serialize};
-use serde_json::from_value;
-use tokio::sync::Mutex;
-use tracing::debug;
+use models::{auth::AuthToken, Paginated, RedoxApiResource, RequestParts, RequestType};
+use redox_core::{util::paths, Deployment};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{spawn, sync::Mutex, task::JoinHandle, time::sleep};
+use tracing::{debug, warn};
 
+mod cassette;
+pub mod diff;
+mod error;
 pub mod key;
 pub mod models;
+pub mod promote;
+mod retry;
+pub mod snapshot;
+mod token_cache;
+
+pub use cassette::{Cassette, CassetteMode, DEFAULT_REDACTED_FIELDS, RECORD_ENV, REPLAY_ENV};
+pub use error::{RedoxApiError, RedoxErrorBody, RedoxErrorDetail};
+pub use retry::RetryConfig;
+use token_cache::TokenCache;
+
+/// Upper bound on the pages followed for a single list request, in case the
+/// API never stops handing out cursors
+const MAX_PAGES: usize = 1000;
+
+/// How many seconds before expiry the background task refreshes the JWT
+const REFRESH_MARGIN: i64 = 60;
+
+/// How long the background task waits before trying again after a failed
+/// refresh
+const REFRESH_FAILURE_DELAY: Duration = Duration::from_secs(30);
 
 #[derive(Serialize)]
 pub struct Claims {
@@ -26,7 +52,7 @@ pub struct Claims {
     exp: i64,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct Jwt {
     token: String,
     expires_at: i64,
@@ -39,13 +65,23 @@ pub struct Auth {
     jwt: Arc<Mutex<Option<Jwt>>>,
 }
 
+/// A client for the Redox API. Clones are cheap and share one connection pool
+/// and one JWT, so a single client can be handed to every task that needs it.
 #[derive(Default, Clone)]
 pub struct RedoxRequestClient {
+    inner: Arc<ClientInner>,
+}
+
+#[derive(Default, Clone)]
+struct ClientInner {
     client: Client,
     base_url: String,
     auth_url: Option<String>,
     key: Key,
     auth: Auth,
+    retry: RetryConfig,
+    token_cache: Option<TokenCache>,
+    cassette: Option<Cassette>,
 }
 
 // Cheap trait implementations to get this working with UserEvents in the TUI
@@ -53,8 +89,13 @@ pub struct RedoxRequestClient {
 impl Debug for RedoxRequestClient {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("RedoxRequestClient")
-            .field("base_url", &self.base_url)
-            .field("auth", &self.auth)
+            .field("base_url", &self.inner.base_url)
+            .field("auth", &self.inner.auth)
+            .field("retry", &self.inner.retry)
+            .field(
+                "cassette",
+                &self.inner.cassette.as_ref().map(Cassette::mode),
+            )
             .finish()
     }
 }
@@ -63,13 +104,12 @@ impl RedoxRequestClient {
     pub fn new(
         base_url: &str,
         auth_url: Option<&str>,
-        key_file: &str,
+        key: Key,
         kid: &str,
         client_id: &str,
-    ) -> anyhow::Result<Self, anyhow::Error> {
-        let key = Key::new(&key_file)?;
+    ) -> Result<Self, RedoxApiError> {
         let client = Client::builder().build()?;
-        Ok(Self {
+        let inner = ClientInner {
             client,
             base_url: base_url.to_string(),
             auth_url: auth_url.map(|s| s.to_string()),
@@ -79,11 +119,183 @@ impl RedoxRequestClient {
                 kid: kid.to_string(),
                 jwt: Arc::new(Mutex::new(None)),
             },
+            retry: RetryConfig::default(),
+            token_cache: None,
+            cassette: None,
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
         })
     }
 
-    fn generate_client_assertion(&self) -> anyhow::Result<String, anyhow::Error> {
-        let mut header = Header::new(Algorithm::RS384);
+    /// Build a client for a configured deployment, used by both the CLI and
+    /// the TUI so they share a token cache. Requests are recorded to or
+    /// replayed from a cassette if `RC_RECORD` or `RC_REPLAY` is set.
+    pub fn for_deployment(deployment: &Deployment) -> Result<Self, RedoxApiError> {
+        let auth = &deployment.auth;
+        let pem = auth.load_private_key().map_err(RedoxApiError::Key)?;
+        let key = Key::from_pem(
+            &pem,
+            auth.algorithm.as_deref(),
+            auth.private_key_passphrase.as_deref(),
+        )
+        .with_context(|| format!("Invalid private key for deployment {}", deployment.name))
+        .map_err(RedoxApiError::Key)?;
+
+        let client = Self::new(
+            &deployment.api_host,
+            deployment.auth_host.as_deref(),
+            key,
+            &auth.kid,
+            &auth.client_id,
+        )?
+        .with_token_cache(paths::token_cache_file(&deployment.name, &auth.client_id));
+        Ok(match Cassette::from_env()? {
+            Some(cassette) => client.with_cassette(cassette),
+            None => client,
+        })
+    }
+
+    /// Persist JWTs to the given file and reuse them until they expire, so
+    /// separate runs of the tool don't each need a new token. A token from a
+    /// different auth host than the client's is never reused.
+    pub fn with_token_cache(mut self, path: PathBuf) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        let host = inner.auth_url.as_deref().unwrap_or(&inner.base_url);
+        inner.token_cache = Some(TokenCache::new(path, host));
+        self
+    }
+
+    /// Replace the default retry behavior for failed requests
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        Arc::make_mut(&mut self.inner).retry = retry;
+        self
+    }
+
+    /// Record requests to a cassette, or answer them from one instead of the
+    /// API. A replaying client never contacts the auth host either.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        Arc::make_mut(&mut self.inner).cassette = Some(cassette);
+        self
+    }
+
+    /// Make sure there is a valid JWT, retrieving a new one if it's missing
+    /// or expired. Concurrent callers share a single refresh: whoever gets
+    /// the lock first does the work, and the rest find the fresh token.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn refresh_jwt(&self) -> Result<(), RedoxApiError> {
+        self.inner.current_jwt().await.map(|_| ())
+    }
+
+    /// Spawn a task that refreshes the JWT shortly before it expires, so
+    /// requests never have to wait on the auth host. The task holds no strong
+    /// reference to the client and stops once every clone has been dropped.
+    pub fn spawn_token_refresh(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        spawn(async move {
+            loop {
+                let Some(client) = inner.upgrade() else {
+                    break;
+                };
+                let delay = match client.jwt_valid_for(REFRESH_MARGIN).await {
+                    Ok(jwt) => {
+                        let remaining = jwt.expires_at - Utc::now().timestamp();
+                        refresh_delay(remaining)
+                    }
+                    Err(e) => {
+                        warn!("background jwt refresh failed. Error: {}", e);
+                        REFRESH_FAILURE_DELAY
+                    }
+                };
+                // Don't keep the client alive while sleeping
+                drop(client);
+                sleep(delay).await;
+            }
+            debug!("client dropped, stopping background jwt refresh");
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn make_request<R>(
+        &self,
+        request_type: RequestType<R::Body>,
+        resource: R,
+    ) -> Result<Response<R>, RedoxApiError>
+    where
+        R: RedoxApiResource + Debug,
+    {
+        let (response, _) = self
+            .make_request_with_payload(request_type, resource)
+            .await?;
+        Ok(response)
+    }
+
+    /// Like [Self::make_request], but also returns the payload as the API
+    /// sent it, including any fields the models don't have. The payloads of
+    /// every page of a list are merged into one. Deleting returns null.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn make_request_with_payload<R>(
+        &self,
+        request_type: RequestType<R::Body>,
+        resource: R,
+    ) -> Result<(Response<R>, Value), RedoxApiError>
+    where
+        R: RedoxApiResource + Debug,
+    {
+        let unsupported = |request| RedoxApiError::Unsupported {
+            request,
+            resource: format!("{resource:?}"),
+        };
+
+        match request_type {
+            RequestType::List => {
+                let (list, payload) = self.inner.collect_pages(&resource).await?;
+                Ok((Response::List(list), payload))
+            }
+            RequestType::Get(id) => {
+                let body = self
+                    .inner
+                    .send_request(resource.build_get_request(&id), None)
+                    .await?;
+                Ok((
+                    Response::Single(parse_payload(&body.payload)?),
+                    body.payload,
+                ))
+            }
+            RequestType::Create(item) => {
+                let request_config = resource
+                    .build_create_request(item)
+                    .ok_or_else(|| unsupported("create"))?;
+                let body = self.inner.send_request(request_config, None).await?;
+                Ok((
+                    Response::Single(parse_payload(&body.payload)?),
+                    body.payload,
+                ))
+            }
+            RequestType::Update(id, item) => {
+                let request_config = resource
+                    .build_update_request(&id, item)
+                    .ok_or_else(|| unsupported("update"))?;
+                let body = self.inner.send_request(request_config, None).await?;
+                Ok((
+                    Response::Single(parse_payload(&body.payload)?),
+                    body.payload,
+                ))
+            }
+            RequestType::Delete(id) => {
+                let request_config = resource
+                    .build_delete_request(&id)
+                    .ok_or_else(|| unsupported("delete"))?;
+                self.inner.send_request(request_config, None).await?;
+                Ok((Response::Empty, Value::Null))
+            }
+        }
+    }
+}
+
+impl ClientInner {
+    fn generate_client_assertion(&self) -> Result<String, RedoxApiError> {
+        let mut header = Header::new(self.key.algorithm());
         header.kid = Some(self.auth.kid.clone());
 
         let now = Utc::now();
@@ -93,11 +305,22 @@ impl RedoxRequestClient {
             iat: now.timestamp(),
             exp: now.timestamp() + (60 * 5),
         };
-        self.key.generate_signed_jwt(&header, &claims)
+        self.key
+            .generate_signed_jwt(&header, &claims)
+            .map_err(RedoxApiError::Key)
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    async fn get_new_jwt(&self) -> anyhow::Result<Jwt, anyhow::Error> {
+    async fn get_new_jwt(&self) -> Result<Jwt, RedoxApiError> {
+        let jwt = self.request_jwt().await?;
+        if let Some(cache) = &self.token_cache {
+            cache.store(&jwt);
+        }
+        Ok(jwt)
+    }
+
+    /// Exchange a fresh client assertion for a JWT from the auth host
+    async fn request_jwt(&self) -> Result<Jwt, RedoxApiError> {
         let jwt = self.generate_client_assertion()?;
         debug!("generated signed assertion");
 
@@ -106,14 +329,7 @@ impl RedoxRequestClient {
             None => &self.base_url,
         };
 
-        let response_jwt = AuthToken::get_auth_token(url, &self.client, &jwt)
-            .await
-            .map_err(|e| {
-                anyhow!(format!(
-                    "Http failure in retrieving JWT. Status Code: {}.",
-                    e.status().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
-                ))
-            })?;
+        let response_jwt = AuthToken::get_auth_token(url, &self.client, &jwt).await?;
         debug!("retrieved auth token");
         Ok(Jwt {
             token: response_jwt.access_token,
@@ -121,80 +337,351 @@ impl RedoxRequestClient {
         })
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub async fn refresh_jwt(&mut self) -> anyhow::Result<(), anyhow::Error> {
+    /// Get the stored JWT, first retrieving a new one if it's missing or
+    /// expired
+    async fn current_jwt(&self) -> Result<Jwt, RedoxApiError> {
+        self.jwt_valid_for(0).await
+    }
+
+    /// Get the stored JWT, first retrieving a new one if it's missing or
+    /// expires within the given number of seconds. The lock is held for the
+    /// whole refresh, so callers arriving mid-refresh wait for its result
+    /// instead of starting their own.
+    async fn jwt_valid_for(&self, seconds: i64) -> Result<Jwt, RedoxApiError> {
+        if self.is_replaying() {
+            // Replayed requests don't need a real token
+            return Ok(Jwt {
+                token: String::new(),
+                expires_at: Utc::now().timestamp() + 24 * 60 * 60,
+            });
+        }
+
         let mut current_jwt = self.auth.jwt.lock().await;
         debug!("unlocked jwt");
 
-        if current_jwt.is_none()
-            || current_jwt.as_ref().unwrap().expires_at < Utc::now().timestamp()
+        if current_jwt.is_none() {
+            *current_jwt = self.token_cache.as_ref().and_then(TokenCache::load);
+        }
+
+        match current_jwt.as_ref() {
+            Some(jwt) if jwt.expires_at - seconds >= Utc::now().timestamp() => Ok(jwt.clone()),
+            _ => {
+                debug!("refreshing jwt");
+                let new_jwt = self.get_new_jwt().await?;
+                *current_jwt = Some(new_jwt.clone());
+                Ok(new_jwt)
+            }
+        }
+    }
+
+    /// Replace a JWT the API rejected, even though it hasn't expired yet. If
+    /// the stored token is no longer the rejected one, it has already been
+    /// replaced and is left alone.
+    async fn force_refresh_jwt(&self, rejected: &Jwt) -> Result<(), RedoxApiError> {
+        let mut current_jwt = self.auth.jwt.lock().await;
+
+        if current_jwt
+            .as_ref()
+            .is_none_or(|jwt| jwt.token == rejected.token)
         {
-            debug!("refreshing jwt");
-            let new_jwt = self.get_new_jwt().await?;
-            *current_jwt = Some(new_jwt);
+            debug!("forcing jwt refresh");
+            *current_jwt = Some(self.get_new_jwt().await?);
         }
 
         Ok(())
     }
 
-    #[tracing::instrument(level = "debug", skip_all)]
-    pub async fn make_request<R>(
-        &mut self,
-        request_type: RequestType,
-        resource: R,
-    ) -> anyhow::Result<Response<R>, anyhow::Error>
+    /// Follow the page cursors of a list endpoint, collecting every page into
+    /// a single list, along with the pages' payloads merged the same way
+    async fn collect_pages<R>(&self, resource: &R) -> Result<(R::List, Value), RedoxApiError>
     where
-        R: RedoxApiResource + Debug,
+        R: RedoxApiResource,
     {
-        self.refresh_jwt().await?;
-
-        if let Some(jwt) = self.auth.jwt.lock().await.clone() {
-            let request_config = match request_type {
-                RequestType::List => resource.build_list_request(),
-            };
+        let first_page = self
+            .send_request(resource.build_list_request(), None)
+            .await?;
+        let mut cursor = first_page.meta.next_cursor();
+        let mut list: R::List = parse_payload(&first_page.payload)?;
+        let mut payload = first_page.payload;
 
-            let url = format!("{}/{}", self.base_url, request_config.path);
-            let request = match request_config.method {
-                Method::GET => self.client.get(&url),
-                _ => unimplemented!(),
-            };
+        let mut pages = 1;
+        while let Some(next_cursor) = cursor {
+            if pages >= MAX_PAGES {
+                warn!(
+                    "stopped paging after {} pages, results are truncated",
+                    pages
+                );
+                break;
+            }
 
-            debug!("sending request to: {}", url);
-            let response = request
-                .header("Authorization", format!("Bearer {}", jwt.token))
-                .send()
+            let page = self
+                .send_request(resource.build_list_request(), Some(&next_cursor))
                 .await?;
+            debug!("retrieved page {}", pages + 1);
+            cursor = page.meta.next_cursor();
+            // Guard against an API handing back the same cursor forever
+            if cursor.as_ref() == Some(&next_cursor) {
+                cursor = None;
+            }
+            list.extend_page(parse_payload(&page.payload)?);
+            extend_payload(&mut payload, page.payload);
+            pages += 1;
+        }
 
-            let response_body = response.json::<GeneralApiResponse>().await?;
-            debug!("parsed raw response");
+        Ok((list, payload))
+    }
 
-            match request_type {
-                RequestType::List => {
-                    let list = from_value(response_body.payload)?;
-                    Ok(Response::List(list))
-                } // _ => {
-                  //     let item = from_value(response_body.payload)?;
-                  //     Ok(Response::Single(item))
-                  // }
+    fn is_replaying(&self) -> bool {
+        self.cassette.as_ref().is_some_and(Cassette::is_replay)
+    }
+
+    /// Send a single request to the API, serializing the body as JSON if there
+    /// is one, and parse the response envelope. The page cursor, if given, is
+    /// passed along to fetch a later page of a list.
+    ///
+    /// With a cassette, the request and its response are recorded, or the
+    /// response is replayed from it without sending anything.
+    async fn send_request<B>(
+        &self,
+        request_config: RequestParts<B>,
+        page_cursor: Option<&str>,
+    ) -> Result<GeneralApiResponse, RedoxApiError>
+    where
+        B: Serialize,
+    {
+        let url = format!("{}/{}", self.base_url, request_config.path);
+        let method = request_config.method.clone();
+
+        let (status, text) = match &self.cassette {
+            Some(cassette) => {
+                let mut query = request_config.query.clone();
+                query.extend(page_cursor.map(|c| ("cursor".to_string(), c.to_string())));
+                let body = request_config
+                    .body
+                    .as_ref()
+                    .map(serde_json::to_value)
+                    .transpose()
+                    .map_err(|e| RedoxApiError::Cassette(e.into()))?;
+
+                if cassette.is_replay() {
+                    cassette.replay_interaction(&method, &request_config.path, &query, body)?
+                } else {
+                    let path = request_config.path.clone();
+                    let (status, text) = self.fetch(request_config, page_cursor).await?;
+                    cassette.record_interaction(&method, &path, &query, body, status, &text);
+                    (status, text)
+                }
             }
-        } else {
-            Err(anyhow!("No JWT available."))
+            None => self.fetch(request_config, page_cursor).await?,
+        };
+
+        if !status.is_success() {
+            return Err(RedoxApiError::Http {
+                method,
+                url,
+                status,
+                error: RedoxErrorBody::parse(&text),
+                raw: text,
+            });
         }
+
+        if text.trim().is_empty() {
+            // Deletes in particular may come back without an envelope
+            return Ok(GeneralApiResponse::default());
+        }
+        let response_body = serde_json::from_str::<GeneralApiResponse>(&text)
+            .map_err(|e| RedoxApiError::deserialize(e, text))?;
+        debug!("parsed raw response");
+        Ok(response_body)
+    }
+
+    /// Send a request over the network, returning the final status and body.
+    ///
+    /// Failures that are likely to be temporary are retried according to the
+    /// client's [RetryConfig]. A 401 gets one retry with a fresh JWT, in case
+    /// the server revoked ours before it expired.
+    async fn fetch<B>(
+        &self,
+        request_config: RequestParts<B>,
+        page_cursor: Option<&str>,
+    ) -> Result<(StatusCode, String), RedoxApiError>
+    where
+        B: Serialize,
+    {
+        let url = format!("{}/{}", self.base_url, request_config.path);
+        let method = request_config.method;
+
+        let mut retry = 0;
+        let mut refreshed_jwt = false;
+        let response = loop {
+            let jwt = self.current_jwt().await?;
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .header("Authorization", format!("Bearer {}", jwt.token));
+            if !request_config.query.is_empty() {
+                request = request.query(&request_config.query);
+            }
+            if let Some(cursor) = page_cursor {
+                request = request.query(&[("cursor", cursor)]);
+            }
+            if let Some(body) = &request_config.body {
+                request = request.json(body);
+            }
+
+            debug!("sending {} request to: {}", method, url);
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => match self.retry.delay_for_error(&method, &e, retry) {
+                    Some(delay) => {
+                        warn!("request failed, retrying in {:?}. Error: {}", delay, e);
+                        sleep(delay).await;
+                        retry += 1;
+                        continue;
+                    }
+                    None => return Err(e.into()),
+                },
+            };
+
+            if response.status() == StatusCode::UNAUTHORIZED && !refreshed_jwt {
+                self.force_refresh_jwt(&jwt).await?;
+                refreshed_jwt = true;
+                continue;
+            }
+
+            let delay = self.retry.delay_for_response(
+                &method,
+                response.status(),
+                response.headers(),
+                retry,
+            );
+            if let Some(delay) = delay {
+                warn!(
+                    "request failed with status {}, retrying in {:?}",
+                    response.status(),
+                    delay
+                );
+                sleep(delay).await;
+                retry += 1;
+                continue;
+            }
+
+            break response;
+        };
+
+        let status = response.status();
+        let text = response.text().await?;
+        Ok((status, text))
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Default, Deserialize, Serialize)]
 pub struct Meta {
     version: String,
+    /// Only present on list responses that have been split into pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageMeta>,
+}
+
+impl Meta {
+    /// The cursor for the following page, if there is one
+    fn next_cursor(&self) -> Option<String> {
+        self.page.as_ref().and_then(|p| p.next_cursor.clone())
+    }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Default, Deserialize, Serialize)]
+pub struct PageMeta {
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    pub size: Option<u32>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
 pub struct GeneralApiResponse {
     pub meta: Meta,
-    pub payload: serde_json::Value,
+    pub payload: Value,
+}
+
+/// Deserialize a response payload into the expected model, keeping the raw
+/// payload around if it doesn't fit
+fn parse_payload<T>(payload: &Value) -> Result<T, RedoxApiError>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(payload).map_err(|e| RedoxApiError::deserialize(e, payload.clone()))
+}
+
+/// Add the items of a later page's payload to the lists in the first page's,
+/// as [Paginated::extend_page] does for the models
+fn extend_payload(payload: &mut Value, next: Value) {
+    let (Value::Object(fields), Value::Object(next)) = (payload, next) else {
+        return;
+    };
+    for (key, value) in next {
+        match (fields.get_mut(&key), value) {
+            (Some(Value::Array(items)), Value::Array(more)) => items.extend(more),
+            (None, value) => {
+                fields.insert(key, value);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How long the background task waits before refreshing a JWT that expires in
+/// `remaining` seconds. Tokens that don't outlive the margin are refreshed
+/// halfway through instead, but never more often than a failed refresh is
+/// retried.
+fn refresh_delay(remaining: i64) -> Duration {
+    if remaining > REFRESH_MARGIN {
+        Duration::from_secs((remaining - REFRESH_MARGIN) as u64)
+    } else {
+        Duration::from_secs((remaining.max(0) / 2) as u64).max(REFRESH_FAILURE_DELAY)
+    }
 }
 
 pub enum Response<R: RedoxApiResource> {
     Single(R::Item),
     List(R::List),
+    /// The request succeeded but has nothing to return, e.g. a delete
+    Empty,
+}
+
+impl<R: RedoxApiResource> Response<R> {
+    /// The item from a get, create or update request
+    pub fn into_single(self) -> Option<R::Item> {
+        match self {
+            Self::Single(item) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// The items from a list request
+    pub fn into_list(self) -> Option<R::List> {
+        match self {
+            Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refreshes_long_lived_tokens_a_margin_before_expiry() {
+        assert_eq!(refresh_delay(3600), Duration::from_secs(3600 - 60));
+        assert_eq!(refresh_delay(61), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn refreshes_short_lived_tokens_halfway_but_not_too_often() {
+        assert_eq!(refresh_delay(60), REFRESH_FAILURE_DELAY);
+        assert_eq!(refresh_delay(50), REFRESH_FAILURE_DELAY);
+        assert_eq!(refresh_delay(10), REFRESH_FAILURE_DELAY);
+        // already expired
+        assert_eq!(refresh_delay(-5), REFRESH_FAILURE_DELAY);
+    }
 }
@@ -2,8 +2,18 @@ use reqwest::Method;
 use serde::{de::DeserializeOwned, Serialize};
 use strum::{Display, EnumIter};
 
+pub mod alert;
 pub mod auth;
+pub mod auth_credential;
+pub mod config_modifier;
+pub mod destination;
+pub mod endpoint;
 pub mod environment;
+pub mod filter;
+pub mod log;
+pub mod organization;
+pub mod source;
+pub mod translation_set;
 
 pub struct RequestParts<T>
 where
@@ -11,22 +21,63 @@ where
 {
     pub path: String,
     pub method: Method,
+    /// Query parameters, such as filters for a list request
+    pub query: Vec<(String, String)>,
     pub body: Option<T>,
 }
 
+/// A list payload that the API may split across several pages
+pub trait Paginated {
+    /// Append the items from the following page onto this one
+    fn extend_page(&mut self, next: Self);
+}
+
 pub trait RedoxApiResource {
     type Item: DeserializeOwned;
-    type List: DeserializeOwned;
+    type List: DeserializeOwned + Paginated;
+    /// The body sent to the API when creating or updating an item
+    type Body: Serialize;
 
     fn build_list_request(&self) -> RequestParts<()>;
+    fn build_get_request(&self, id: &str) -> RequestParts<()>;
+
+    /// Build the request to create a new item. Resources that can't be
+    /// created through the API leave this as the default, `None`.
+    fn build_create_request(&self, _body: Self::Body) -> Option<RequestParts<Self::Body>> {
+        None
+    }
+
+    /// Build the request to update an existing item. Resources that can't be
+    /// updated through the API leave this as the default, `None`.
+    fn build_update_request(
+        &self,
+        _id: &str,
+        _body: Self::Body,
+    ) -> Option<RequestParts<Self::Body>> {
+        None
+    }
+
+    /// Build the request to delete an item. Resources that can't be deleted
+    /// through the API leave this as the default, `None`.
+    fn build_delete_request(&self, _id: &str) -> Option<RequestParts<()>> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum RequestType {
+pub enum RequestType<B = ()> {
     List,
+    /// Fetch a single item by its ID
+    Get(String),
+    /// Create a new item from the given body
+    Create(B),
+    /// Update the item with the given ID from the given body
+    Update(String, B),
+    /// Delete the item with the given ID
+    Delete(String),
 }
 
-#[derive(EnumIter, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display)]
 pub enum EnvironmentResources {
     Alerts,
     #[strum(to_string = "Auth credential")]
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use super::{Paginated, RedoxApiResource, RequestParts};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum AlertStatus {
+    Open,
+    Acknowledged,
+    Resolved,
+    /// A status this version of the tool doesn't know about
+    #[serde(other)]
+    #[strum(disabled)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+    /// A severity this version of the tool doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Alert {
+    pub id: String,
+    pub name: String,
+    pub status: AlertStatus,
+    pub severity: AlertSeverity,
+    pub message: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// The log that triggered the alert, if there was one
+    #[serde(rename = "logId")]
+    pub log_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlertList {
+    pub alerts: Vec<Alert>,
+}
+
+impl Paginated for AlertList {
+    fn extend_page(&mut self, next: Self) {
+        self.alerts.extend(next.alerts);
+    }
+}
+
+/// Alerts can't be edited, only moved along to a new status
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertStatusBody {
+    pub status: AlertStatus,
+}
+
+impl AlertStatusBody {
+    pub fn acknowledge() -> Self {
+        Self {
+            status: AlertStatus::Acknowledged,
+        }
+    }
+
+    pub fn resolve() -> Self {
+        Self {
+            status: AlertStatus::Resolved,
+        }
+    }
+}
+
+/// Alerts raised for an environment. Use an update request with an
+/// [AlertStatusBody] to acknowledge or resolve one.
+#[derive(Debug, Clone)]
+pub struct AlertResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl AlertResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/alerts",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for AlertResource {
+    type Item = Alert;
+    type List = AlertList;
+    type Body = AlertStatusBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: AlertStatusBody,
+    ) -> Option<RequestParts<AlertStatusBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PATCH,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+}
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use reqwest::Client;
 use serde::Deserialize;
 
+use crate::error::{RedoxApiError, RedoxErrorBody};
+
 #[derive(Deserialize)]
 pub struct AuthTokenResponse {
     pub access_token: String,
@@ -18,7 +20,7 @@ impl AuthToken {
         base_url: &str,
         client: &Client,
         client_assertion: &str,
-    ) -> Result<AuthTokenResponse, reqwest::Error> {
+    ) -> Result<AuthTokenResponse, RedoxApiError> {
         let url = format!("{}/v2/auth/token", base_url);
 
         let mut form_body = HashMap::new();
@@ -29,14 +31,18 @@ impl AuthToken {
         );
         form_body.insert("client_assertion".to_string(), client_assertion.to_string());
 
-        let response = client
-            .post(&url)
-            .form(&form_body)
-            .send()
-            .await?
-            .error_for_status()?
-            .json::<AuthTokenResponse>()
-            .await?;
-        Ok(response)
+        let response = client.post(&url).form(&form_body).send().await?;
+        let status = response.status();
+        let raw = response.text().await?;
+
+        if !status.is_success() {
+            return Err(RedoxApiError::Auth {
+                status,
+                error: RedoxErrorBody::parse(&raw),
+                raw,
+            });
+        }
+
+        serde_json::from_str(&raw).map_err(|e| RedoxApiError::deserialize(e, raw))
     }
 }
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{Paginated, RedoxApiResource, RequestParts};
+use crate::key::{Jwk, Jwks};
+
+/// An API credential for an environment. Clients authenticate by signing a
+/// client assertion with a private key whose public half is in the
+/// credential's key set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuthCredential {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "clientId")]
+    pub client_id: String,
+    #[serde(rename = "publicKeys", default)]
+    pub public_keys: Jwks,
+    /// Set instead of the public keys when Redox fetches them from a URL
+    #[serde(rename = "jwksUrl")]
+    pub jwks_url: Option<String>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl AuthCredential {
+    /// The IDs of the keys Redox will accept for this credential
+    pub fn kids(&self) -> impl Iterator<Item = &str> {
+        self.public_keys.keys.iter().map(|k| k.kid.as_str())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuthCredentialList {
+    #[serde(rename = "authCredentials")]
+    pub auth_credentials: Vec<AuthCredential>,
+}
+
+impl Paginated for AuthCredentialList {
+    fn extend_page(&mut self, next: Self) {
+        self.auth_credentials.extend(next.auth_credentials);
+    }
+}
+
+/// The fields that can be set when creating or updating a credential. An
+/// update replaces the whole key set.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthCredentialBody {
+    pub name: String,
+    #[serde(rename = "publicKeys")]
+    pub public_keys: Jwks,
+    #[serde(rename = "jwksUrl", skip_serializing_if = "Option::is_none")]
+    pub jwks_url: Option<String>,
+    #[serde(rename = "expiresAt", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl AuthCredentialBody {
+    /// Add a key to the set, replacing any key with the same kid
+    pub fn add_key(&mut self, jwk: Jwk) {
+        self.remove_key(&jwk.kid);
+        self.public_keys.keys.push(jwk);
+    }
+
+    /// Remove the key with the given kid from the set, if it's there
+    pub fn remove_key(&mut self, kid: &str) {
+        self.public_keys.keys.retain(|k| k.kid != kid);
+    }
+}
+
+impl From<AuthCredential> for AuthCredentialBody {
+    fn from(credential: AuthCredential) -> Self {
+        Self {
+            name: credential.name,
+            public_keys: credential.public_keys,
+            jwks_url: credential.jwks_url,
+            expires_at: credential.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthCredentialResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl AuthCredentialResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/auth-credentials",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for AuthCredentialResource {
+    type Item = AuthCredential;
+    type List = AuthCredentialList;
+    type Body = AuthCredentialBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(
+        &self,
+        body: AuthCredentialBody,
+    ) -> Option<RequestParts<AuthCredentialBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: AuthCredentialBody,
+    ) -> Option<RequestParts<AuthCredentialBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
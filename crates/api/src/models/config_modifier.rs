@@ -0,0 +1,143 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{endpoint::AttachedEndpoints, Paginated, RedoxApiResource, RequestParts};
+
+/// Rewrites fields of the data passing through, for example to default a
+/// value a destination requires
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfigModifier {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "dataModel")]
+    pub data_model: Option<String>,
+    #[serde(rename = "eventType")]
+    pub event_type: Option<String>,
+    /// The changes applied to each payload, kept as the API returns them
+    #[serde(default)]
+    pub modifications: Value,
+    #[serde(rename = "attachedTo", default)]
+    pub attached_to: AttachedEndpoints,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConfigModifierList {
+    #[serde(rename = "configModifiers")]
+    pub config_modifiers: Vec<ConfigModifier>,
+}
+
+impl Paginated for ConfigModifierList {
+    fn extend_page(&mut self, next: Self) {
+        self.config_modifiers.extend(next.config_modifiers);
+    }
+}
+
+/// The fields that can be set when creating or updating a config modifier
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigModifierBody {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "dataModel", skip_serializing_if = "Option::is_none")]
+    pub data_model: Option<String>,
+    #[serde(rename = "eventType", skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    pub modifications: Value,
+    #[serde(rename = "attachedTo")]
+    pub attached_to: AttachedEndpoints,
+}
+
+impl From<ConfigModifier> for ConfigModifierBody {
+    fn from(modifier: ConfigModifier) -> Self {
+        Self {
+            name: modifier.name,
+            description: modifier.description,
+            data_model: modifier.data_model,
+            event_type: modifier.event_type,
+            modifications: modifier.modifications,
+            attached_to: modifier.attached_to,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigModifierResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl ConfigModifierResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/config-modifiers",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for ConfigModifierResource {
+    type Item = ConfigModifier;
+    type List = ConfigModifierList;
+    type Body = ConfigModifierBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(
+        &self,
+        body: ConfigModifierBody,
+    ) -> Option<RequestParts<ConfigModifierBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: ConfigModifierBody,
+    ) -> Option<RequestParts<ConfigModifierBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
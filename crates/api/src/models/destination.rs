@@ -0,0 +1,139 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    endpoint::{DataModelSubscription, EndpointAuth},
+    Paginated, RedoxApiResource, RequestParts,
+};
+
+/// A system that Redox sends data to
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Destination {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Where Redox sends data for this destination
+    pub endpoint: Option<String>,
+    /// Sent by Redox with every request so the destination can verify it
+    #[serde(rename = "verificationToken")]
+    pub verification_token: Option<String>,
+    pub auth: Option<EndpointAuth>,
+    #[serde(default)]
+    pub subscriptions: Vec<DataModelSubscription>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DestinationList {
+    pub destinations: Vec<Destination>,
+}
+
+impl Paginated for DestinationList {
+    fn extend_page(&mut self, next: Self) {
+        self.destinations.extend(next.destinations);
+    }
+}
+
+/// The fields that can be set when creating or updating a destination
+#[derive(Debug, Clone, Serialize)]
+pub struct DestinationBody {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(rename = "verificationToken", skip_serializing_if = "Option::is_none")]
+    pub verification_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<EndpointAuth>,
+    pub subscriptions: Vec<DataModelSubscription>,
+}
+
+impl From<Destination> for DestinationBody {
+    fn from(destination: Destination) -> Self {
+        Self {
+            name: destination.name,
+            description: destination.description,
+            endpoint: destination.endpoint,
+            verification_token: destination.verification_token,
+            auth: destination.auth,
+            subscriptions: destination.subscriptions,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DestinationResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl DestinationResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/destinations",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for DestinationResource {
+    type Item = Destination;
+    type List = DestinationList;
+    type Body = DestinationBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(&self, body: DestinationBody) -> Option<RequestParts<DestinationBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: DestinationBody,
+    ) -> Option<RequestParts<DestinationBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How Redox authenticates with an endpoint outside of Redox
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EndpointAuth {
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    #[serde(rename = "oauth")]
+    OAuth {
+        #[serde(rename = "tokenUrl")]
+        token_url: String,
+        #[serde(rename = "clientId")]
+        client_id: String,
+        #[serde(
+            rename = "clientSecret",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        client_secret: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        scope: Option<String>,
+    },
+}
+
+/// A data model and the event types within it that a source sends or a
+/// destination receives
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DataModelSubscription {
+    #[serde(rename = "dataModel")]
+    pub data_model: String,
+    /// Empty means every event type in the data model
+    #[serde(rename = "eventTypes", default)]
+    pub event_types: Vec<String>,
+}
+
+/// The sources and destinations a filter or config modifier applies to, by ID
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AttachedEndpoints {
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub destinations: Vec<String>,
+}
+
+impl AttachedEndpoints {
+    /// Swap each ID for the one it maps to, e.g. when copying configuration
+    /// to another environment. IDs without a mapping are kept as they are.
+    pub fn remapped(&self, ids: &HashMap<String, String>) -> Self {
+        let remap = |list: &[String]| {
+            list.iter()
+                .map(|id| ids.get(id).unwrap_or(id).clone())
+                .collect()
+        };
+        Self {
+            sources: remap(&self.sources),
+            destinations: remap(&self.destinations),
+        }
+    }
+}
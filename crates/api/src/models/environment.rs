@@ -1,21 +1,33 @@
 use reqwest::Method;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{RedoxApiResource, RequestParts};
+use super::{Paginated, RedoxApiResource, RequestParts};
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub enum EnvironmentFlag {
     Production,
     Staging,
     Development,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl EnvironmentFlag {
+    /// The kind of environment configuration is usually promoted to from
+    /// this one. Nothing comes after production.
+    pub fn promotes_to(&self) -> Option<Self> {
+        match self {
+            Self::Development => Some(Self::Staging),
+            Self::Staging => Some(Self::Production),
+            Self::Production => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OrgObj {
     pub id: i32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Environment {
     pub name: String,
     #[serde(rename = "environmentFlag")]
@@ -24,11 +36,33 @@ pub struct Environment {
     pub organization: OrgObj,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Environment {
+    /// Whether this is the environment with the given ID, or failing that,
+    /// the given name in any case
+    pub fn matches(&self, id_or_name: &str) -> bool {
+        self.id == id_or_name || self.name.eq_ignore_ascii_case(id_or_name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EnvironmentList {
     pub environments: Vec<Environment>,
 }
 
+impl Paginated for EnvironmentList {
+    fn extend_page(&mut self, next: Self) {
+        self.environments.extend(next.environments);
+    }
+}
+
+/// The fields that can be set when creating or updating an environment
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentBody {
+    pub name: String,
+    #[serde(rename = "environmentFlag")]
+    pub environment_flag: EnvironmentFlag,
+}
+
 #[derive(Debug, Clone)]
 pub struct EnvironmentResource {
     org_id: i32,
@@ -43,15 +77,63 @@ impl EnvironmentResource {
 impl RedoxApiResource for EnvironmentResource {
     type Item = Environment;
     type List = EnvironmentList;
+    type Body = EnvironmentBody;
 
     fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: format!("platform/v1/organizations/{}/environments", self.org_id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
         RequestParts {
             path: format!(
-                "platform/v1/organizations/{}/environments",
-                self.org_id.to_string()
+                "platform/v1/organizations/{}/environments/{}",
+                self.org_id, id
             ),
             method: Method::GET,
+            query: Vec::new(),
             body: None,
         }
     }
+
+    fn build_create_request(&self, body: EnvironmentBody) -> Option<RequestParts<EnvironmentBody>> {
+        Some(RequestParts {
+            path: format!("platform/v1/organizations/{}/environments", self.org_id),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: EnvironmentBody,
+    ) -> Option<RequestParts<EnvironmentBody>> {
+        Some(RequestParts {
+            path: format!(
+                "platform/v1/organizations/{}/environments/{}",
+                self.org_id, id
+            ),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!(
+                "platform/v1/organizations/{}/environments/{}",
+                self.org_id, id
+            ),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
 }
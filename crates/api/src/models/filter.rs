@@ -0,0 +1,135 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{endpoint::AttachedEndpoints, Paginated, RedoxApiResource, RequestParts};
+
+/// Drops data that matches its rules before it reaches a destination
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Filter {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "dataModel")]
+    pub data_model: Option<String>,
+    /// Empty means every event type in the data model
+    #[serde(rename = "eventTypes", default)]
+    pub event_types: Vec<String>,
+    /// The filter logic, kept as the API returns it
+    #[serde(default)]
+    pub rules: Value,
+    #[serde(rename = "attachedTo", default)]
+    pub attached_to: AttachedEndpoints,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FilterList {
+    pub filters: Vec<Filter>,
+}
+
+impl Paginated for FilterList {
+    fn extend_page(&mut self, next: Self) {
+        self.filters.extend(next.filters);
+    }
+}
+
+/// The fields that can be set when creating or updating a filter
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterBody {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "dataModel", skip_serializing_if = "Option::is_none")]
+    pub data_model: Option<String>,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Vec<String>,
+    pub rules: Value,
+    #[serde(rename = "attachedTo")]
+    pub attached_to: AttachedEndpoints,
+}
+
+impl From<Filter> for FilterBody {
+    fn from(filter: Filter) -> Self {
+        Self {
+            name: filter.name,
+            description: filter.description,
+            data_model: filter.data_model,
+            event_types: filter.event_types,
+            rules: filter.rules,
+            attached_to: filter.attached_to,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FilterResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl FilterResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/filters",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for FilterResource {
+    type Item = Filter;
+    type List = FilterList;
+    type Body = FilterBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(&self, body: FilterBody) -> Option<RequestParts<FilterBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(&self, id: &str, body: FilterBody) -> Option<RequestParts<FilterBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum::{Display, EnumString};
+
+use super::{Paginated, RedoxApiResource, RequestParts};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum LogStatus {
+    Success,
+    Failed,
+    Processing,
+    Filtered,
+    /// A status this version of the tool doesn't know about
+    #[serde(other)]
+    #[strum(disabled)]
+    Unknown,
+}
+
+/// A source or destination a log passed through
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogEndpoint {
+    pub id: String,
+    pub name: String,
+}
+
+/// The summary of a log returned when listing logs
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Log {
+    pub id: String,
+    pub status: LogStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    pub source: Option<LogEndpoint>,
+    pub destination: Option<LogEndpoint>,
+    #[serde(rename = "dataModel")]
+    pub data_model: Option<String>,
+    #[serde(rename = "eventType")]
+    pub event_type: Option<String>,
+}
+
+/// One step a log went through on its way from the source to the destination
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogStage {
+    pub name: String,
+    pub status: LogStatus,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The payload this stage received, as sent by the previous stage
+    #[serde(default)]
+    pub request: Option<Value>,
+    /// The payload this stage produced, or the error it failed with
+    #[serde(default)]
+    pub response: Option<Value>,
+}
+
+/// A single log, including the payloads for every stage it went through
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogDetail {
+    #[serde(flatten)]
+    pub log: Log,
+    #[serde(default)]
+    pub stages: Vec<LogStage>,
+}
+
+impl LogDetail {
+    /// The first stage that failed, which is usually the one worth looking at
+    pub fn failed_stage(&self) -> Option<&LogStage> {
+        self.stages.iter().find(|s| s.status == LogStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogList {
+    pub logs: Vec<Log>,
+}
+
+impl Paginated for LogList {
+    fn extend_page(&mut self, next: Self) {
+        self.logs.extend(next.logs);
+    }
+}
+
+/// Narrows down a log list. The filtering happens on the server, so only
+/// matching logs are paged through.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Only logs created at or after this time
+    pub start: Option<DateTime<Utc>>,
+    /// Only logs created before this time
+    pub end: Option<DateTime<Utc>>,
+    pub status: Option<LogStatus>,
+    pub source_id: Option<String>,
+    pub destination_id: Option<String>,
+    pub data_model: Option<String>,
+    pub event_type: Option<String>,
+}
+
+impl LogFilter {
+    fn to_query(&self) -> Vec<(String, String)> {
+        let timestamp = |t: &DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        [
+            ("startDate", self.start.as_ref().map(timestamp)),
+            ("endDate", self.end.as_ref().map(timestamp)),
+            ("status", self.status.map(|s| s.to_string())),
+            ("sourceId", self.source_id.clone()),
+            ("destinationId", self.destination_id.clone()),
+            ("dataModel", self.data_model.clone()),
+            ("eventType", self.event_type.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| (name.to_string(), v)))
+        .collect()
+    }
+}
+
+/// Logs for an environment. Logs are created by Redox as data flows through,
+/// so they can only be listed and fetched.
+#[derive(Debug, Clone)]
+pub struct LogResource {
+    org_id: i32,
+    environment_id: String,
+    filter: LogFilter,
+}
+
+impl LogResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+            filter: LogFilter::default(),
+        }
+    }
+
+    /// Only list logs matching the filter
+    pub fn with_filter(mut self, filter: LogFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/logs",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for LogResource {
+    type Item = LogDetail;
+    type List = LogList;
+    type Body = ();
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: self.filter.to_query(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+}
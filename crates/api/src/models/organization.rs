@@ -0,0 +1,55 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{Paginated, RedoxApiResource, RequestParts};
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Organization {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrganizationList {
+    pub organizations: Vec<Organization>,
+}
+
+impl Paginated for OrganizationList {
+    fn extend_page(&mut self, next: Self) {
+        self.organizations.extend(next.organizations);
+    }
+}
+
+/// The organizations the current credential has access to
+#[derive(Debug, Clone, Default)]
+pub struct OrganizationResource;
+
+impl OrganizationResource {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl RedoxApiResource for OrganizationResource {
+    type Item = Organization;
+    type List = OrganizationList;
+    type Body = ();
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: "platform/v1/organizations".to_string(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("platform/v1/organizations/{}", id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    endpoint::{DataModelSubscription, EndpointAuth},
+    Paginated, RedoxApiResource, RequestParts,
+};
+
+/// A system that sends data to Redox
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Source {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Where Redox sends responses and acknowledgements back to the source
+    pub endpoint: Option<String>,
+    /// Sent by Redox with every request so the source can verify it
+    #[serde(rename = "verificationToken")]
+    pub verification_token: Option<String>,
+    pub auth: Option<EndpointAuth>,
+    #[serde(default)]
+    pub subscriptions: Vec<DataModelSubscription>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SourceList {
+    pub sources: Vec<Source>,
+}
+
+impl Paginated for SourceList {
+    fn extend_page(&mut self, next: Self) {
+        self.sources.extend(next.sources);
+    }
+}
+
+/// The fields that can be set when creating or updating a source
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceBody {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(rename = "verificationToken", skip_serializing_if = "Option::is_none")]
+    pub verification_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<EndpointAuth>,
+    pub subscriptions: Vec<DataModelSubscription>,
+}
+
+impl From<Source> for SourceBody {
+    fn from(source: Source) -> Self {
+        Self {
+            name: source.name,
+            description: source.description,
+            endpoint: source.endpoint,
+            verification_token: source.verification_token,
+            auth: source.auth,
+            subscriptions: source.subscriptions,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl SourceResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/sources",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for SourceResource {
+    type Item = Source;
+    type List = SourceList;
+    type Body = SourceBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(&self, body: SourceBody) -> Option<RequestParts<SourceBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(&self, id: &str, body: SourceBody) -> Option<RequestParts<SourceBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, bail};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{Paginated, RedoxApiResource, RequestParts};
+
+/// Translates one code from the source code system to the target code system
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CodeMapping {
+    #[serde(rename = "sourceCode")]
+    pub source_code: String,
+    #[serde(rename = "sourceDisplay", default)]
+    pub source_display: Option<String>,
+    #[serde(rename = "targetCode")]
+    pub target_code: String,
+    #[serde(rename = "targetDisplay", default)]
+    pub target_display: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TranslationSet {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "sourceCodeSystem")]
+    pub source_code_system: Option<String>,
+    #[serde(rename = "targetCodeSystem")]
+    pub target_code_system: Option<String>,
+    /// Left empty by the list endpoint, fetch the set to get its mappings
+    #[serde(default)]
+    pub mappings: Vec<CodeMapping>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TranslationSetList {
+    #[serde(rename = "translationSets")]
+    pub translation_sets: Vec<TranslationSet>,
+}
+
+impl Paginated for TranslationSetList {
+    fn extend_page(&mut self, next: Self) {
+        self.translation_sets.extend(next.translation_sets);
+    }
+}
+
+/// The fields that can be set when creating or updating a translation set.
+/// An update replaces every mapping in the set.
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationSetBody {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "sourceCodeSystem", skip_serializing_if = "Option::is_none")]
+    pub source_code_system: Option<String>,
+    #[serde(rename = "targetCodeSystem", skip_serializing_if = "Option::is_none")]
+    pub target_code_system: Option<String>,
+    pub mappings: Vec<CodeMapping>,
+}
+
+impl From<TranslationSet> for TranslationSetBody {
+    fn from(set: TranslationSet) -> Self {
+        Self {
+            name: set.name,
+            description: set.description,
+            source_code_system: set.source_code_system,
+            target_code_system: set.target_code_system,
+            mappings: set.mappings,
+        }
+    }
+}
+
+/// Read code mappings from a CSV with a header row of `sourceCode`,
+/// `sourceDisplay`, `targetCode` and `targetDisplay`. The display columns
+/// may be left empty or out entirely. Every row needs both codes, and a
+/// source code can only be mapped once.
+pub fn mappings_from_csv<R: Read>(reader: R) -> anyhow::Result<Vec<CodeMapping>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| anyhow!("Failed to read code mappings. Error: {}", e))?
+        .clone();
+    let mut seen = HashSet::new();
+    let mut mappings = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| anyhow!("Failed to read code mappings. Error: {}", e))?;
+        // Quoted fields can span lines, so count where the record starts
+        // rather than how many came before it
+        let line = record.position().map_or(0, |position| position.line());
+        let mapping: CodeMapping = record
+            .deserialize(Some(&headers))
+            .map_err(|e| anyhow!("Failed to read line {line} of code mappings. Error: {}", e))?;
+
+        if mapping.source_code.is_empty() || mapping.target_code.is_empty() {
+            bail!("Line {line} is missing a source or target code");
+        }
+        if !seen.insert(mapping.source_code.clone()) {
+            bail!(
+                "Line {line} maps source code {} a second time",
+                mapping.source_code
+            );
+        }
+        mappings.push(mapping);
+    }
+
+    Ok(mappings)
+}
+
+/// Write code mappings as a CSV that [mappings_from_csv] can read back
+pub fn mappings_to_csv<W: Write>(writer: W, mappings: &[CodeMapping]) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for mapping in mappings {
+        writer
+            .serialize(mapping)
+            .map_err(|e| anyhow!("Failed to write code mappings. Error: {}", e))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslationSetResource {
+    org_id: i32,
+    environment_id: String,
+}
+
+impl TranslationSetResource {
+    pub fn new(org_id: i32, environment_id: &str) -> Self {
+        Self {
+            org_id,
+            environment_id: environment_id.to_string(),
+        }
+    }
+
+    fn base_path(&self) -> String {
+        format!(
+            "platform/v1/organizations/{}/environments/{}/translation-sets",
+            self.org_id, self.environment_id
+        )
+    }
+}
+
+impl RedoxApiResource for TranslationSetResource {
+    type Item = TranslationSet;
+    type List = TranslationSetList;
+    type Body = TranslationSetBody;
+
+    fn build_list_request(&self) -> RequestParts<()> {
+        RequestParts {
+            path: self.base_path(),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_get_request(&self, id: &str) -> RequestParts<()> {
+        RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::GET,
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    fn build_create_request(
+        &self,
+        body: TranslationSetBody,
+    ) -> Option<RequestParts<TranslationSetBody>> {
+        Some(RequestParts {
+            path: self.base_path(),
+            method: Method::POST,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_update_request(
+        &self,
+        id: &str,
+        body: TranslationSetBody,
+    ) -> Option<RequestParts<TranslationSetBody>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::PUT,
+            query: Vec::new(),
+            body: Some(body),
+        })
+    }
+
+    fn build_delete_request(&self, id: &str) -> Option<RequestParts<()>> {
+        Some(RequestParts {
+            path: format!("{}/{}", self.base_path(), id),
+            method: Method::DELETE,
+            query: Vec::new(),
+            body: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(source: &str, target: &str, display: Option<&str>) -> CodeMapping {
+        CodeMapping {
+            source_code: source.into(),
+            source_display: display.map(Into::into),
+            target_code: target.into(),
+            target_display: display.map(Into::into),
+        }
+    }
+
+    fn read(csv: &str) -> anyhow::Result<Vec<CodeMapping>> {
+        mappings_from_csv(csv.as_bytes())
+    }
+
+    #[test]
+    fn round_trips() {
+        let mappings = vec![
+            mapping("F", "female", Some("Female")),
+            mapping("U", "unknown", None),
+            mapping("O", "other", Some("Other, \"not listed\"\nor declined")),
+        ];
+        let mut written = Vec::new();
+        mappings_to_csv(&mut written, &mappings).unwrap();
+
+        let written = String::from_utf8(written).unwrap();
+        assert!(written.starts_with("sourceCode,sourceDisplay,targetCode,targetDisplay\n"));
+        assert_eq!(read(&written).unwrap(), mappings);
+    }
+
+    #[test]
+    fn reads_quoted_fields_with_commas() {
+        let csv = "sourceCode,sourceDisplay,targetCode,targetDisplay\n\
+            M,\"Male, adult\",male,\"Male\"\n";
+        assert_eq!(
+            read(csv).unwrap(),
+            [CodeMapping {
+                source_code: "M".into(),
+                source_display: Some("Male, adult".into()),
+                target_code: "male".into(),
+                target_display: Some("Male".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn display_columns_are_optional() {
+        let csv = "targetCode,sourceCode\nfemale,F\n";
+        assert_eq!(read(csv).unwrap(), [mapping("F", "female", None)]);
+    }
+
+    #[test]
+    fn rejects_duplicate_source_codes() {
+        let csv = "sourceCode,targetCode\nF,female\nM,male\nF,woman\n";
+        let error = read(csv).unwrap_err().to_string();
+        assert_eq!(error, "Line 4 maps source code F a second time");
+    }
+
+    #[test]
+    fn reports_the_line_of_a_bad_row() {
+        // the quoted display spans two lines, so the bad row is on line 4
+        let csv = "sourceCode,sourceDisplay,targetCode\n\
+            F,\"Female\nor woman\",female\n\
+            M,Male,\n";
+        let error = read(csv).unwrap_err().to_string();
+        assert_eq!(error, "Line 4 is missing a source or target code");
+
+        // rows with columns missing are reported the same way
+        let csv = "sourceCode,targetCode\nF,female\nM\n";
+        let error = read(csv).unwrap_err().to_string();
+        assert!(error.starts_with("Failed to read line 3 "), "{error}");
+    }
+}
@@ -0,0 +1,446 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    diff::{diff_fields, normalize, FieldDiff},
+    models::endpoint::AttachedEndpoints,
+    snapshot::{
+        ConfigItem, ConfigKind, EnvironmentSnapshot, RestoreAction, RestoreError, RestoredItem,
+    },
+    RedoxApiError, RedoxRequestClient,
+};
+
+/// The filters, config modifiers and translation sets to promote, by name
+#[derive(Debug, Clone, Default)]
+pub struct PromotionItems {
+    pub filters: Vec<String>,
+    pub config_modifiers: Vec<String>,
+    pub translation_sets: Vec<String>,
+}
+
+impl PromotionItems {
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+            && self.config_modifiers.is_empty()
+            && self.translation_sets.is_empty()
+    }
+}
+
+/// The configuration of the environment being promoted from and the one
+/// being promoted to, for planning what to copy between them
+#[derive(Debug, Clone)]
+pub struct Promotion {
+    source: EnvironmentSnapshot,
+    target: EnvironmentSnapshot,
+}
+
+/// What promoting one item would do to the target. For an update, `fields`
+/// are the fields that change, with the target's current value on the left.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromotedItem {
+    pub kind: ConfigKind,
+    pub name: String,
+    pub action: RestoreAction,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// The changes promoting the selected items would make, ready to be applied
+#[derive(Debug, Clone)]
+pub struct PromotionPlan {
+    pub changes: Vec<PromotedItem>,
+    /// Selected items the target already has exactly as they are
+    pub unchanged: Vec<(ConfigKind, String)>,
+    /// Names of the sources and destinations the selected items are attached
+    /// to that the target has no endpoint of the same name for. Their IDs
+    /// can't be remapped, so the plan can't be applied until they exist.
+    pub missing_endpoints: Vec<String>,
+    /// The items to write, as they are in the source
+    items: EnvironmentSnapshot,
+    /// Source endpoint IDs mapped to the target endpoints with the same name
+    endpoint_ids: HashMap<String, String>,
+    target: EnvironmentSnapshot,
+}
+
+impl Promotion {
+    /// Fetch the configuration of both environments
+    pub async fn fetch(
+        client: &RedoxRequestClient,
+        org_id: i32,
+        source_environment_id: &str,
+        target_environment_id: &str,
+    ) -> Result<Self, RedoxApiError> {
+        Ok(Self {
+            source: EnvironmentSnapshot::fetch(client, org_id, source_environment_id).await?,
+            target: EnvironmentSnapshot::fetch(client, org_id, target_environment_id).await?,
+        })
+    }
+
+    /// The configuration being promoted from
+    pub fn source(&self) -> &EnvironmentSnapshot {
+        &self.source
+    }
+
+    /// Work out what promoting the selected items would change. Items are
+    /// matched to the target's by name, and the endpoints filters and config
+    /// modifiers are attached to are matched by name as well. Names that
+    /// aren't in the source are ignored.
+    pub fn plan(&self, selected: &PromotionItems) -> PromotionPlan {
+        let mut planner = Planner {
+            source_names: endpoint_names(&self.source),
+            target_names: endpoint_names(&self.target),
+            changes: Vec::new(),
+            unchanged: Vec::new(),
+        };
+
+        let items = EnvironmentSnapshot {
+            filters: planner.select(
+                &selected.filters,
+                &self.source.filters,
+                &self.target.filters,
+            ),
+            config_modifiers: planner.select(
+                &selected.config_modifiers,
+                &self.source.config_modifiers,
+                &self.target.config_modifiers,
+            ),
+            translation_sets: planner.select(
+                &selected.translation_sets,
+                &self.source.translation_sets,
+                &self.target.translation_sets,
+            ),
+            ..Default::default()
+        };
+
+        // Endpoint IDs in the target by name
+        let target_sources: HashMap<&str, &str> = self
+            .target
+            .sources
+            .iter()
+            .map(|s| (s.name(), s.id()))
+            .collect();
+        let target_destinations: HashMap<&str, &str> = self
+            .target
+            .destinations
+            .iter()
+            .map(|d| (d.name(), d.id()))
+            .collect();
+
+        let attached = items
+            .filters
+            .iter()
+            .map(|f| &f.attached_to)
+            .chain(items.config_modifiers.iter().map(|m| &m.attached_to));
+        let mut endpoint_ids = HashMap::new();
+        let mut missing_endpoints = BTreeSet::new();
+        for AttachedEndpoints {
+            sources,
+            destinations,
+        } in attached
+        {
+            for (ids, target) in [
+                (sources, &target_sources),
+                (destinations, &target_destinations),
+            ] {
+                for id in ids {
+                    let name = planner.source_names.get(id).unwrap_or(id);
+                    match target.get(name.as_str()) {
+                        Some(target_id) => {
+                            endpoint_ids.insert(id.clone(), target_id.to_string());
+                        }
+                        None => {
+                            missing_endpoints.insert(name.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        PromotionPlan {
+            changes: planner.changes,
+            unchanged: planner.unchanged,
+            missing_endpoints: missing_endpoints.into_iter().collect(),
+            items,
+            endpoint_ids,
+            target: self.target.clone(),
+        }
+    }
+}
+
+impl PromotionPlan {
+    /// Whether promoting would change nothing
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Create and update the planned items in the target environment, which
+    /// must be the one the plan was made for. A plan with
+    /// [missing endpoints](Self::missing_endpoints) is refused before anything
+    /// is written, since its attachments would point at another environment's
+    /// IDs. If an item fails, the error has the items that were already
+    /// promoted.
+    pub async fn apply(
+        &self,
+        client: &RedoxRequestClient,
+        org_id: i32,
+        target_environment_id: &str,
+    ) -> Result<Vec<RestoredItem>, RestoreError> {
+        if !self.missing_endpoints.is_empty() {
+            return Err(RedoxApiError::MissingEndpoints(self.missing_endpoints.clone()).into());
+        }
+        self.items
+            .restore_onto(
+                client,
+                org_id,
+                target_environment_id,
+                &self.target,
+                self.endpoint_ids.clone(),
+                false,
+            )
+            .await
+    }
+}
+
+struct Planner {
+    /// Endpoint names by ID in each environment, so attachments compare
+    /// equal when they're to endpoints of the same name
+    source_names: HashMap<String, String>,
+    target_names: HashMap<String, String>,
+    changes: Vec<PromotedItem>,
+    unchanged: Vec<(ConfigKind, String)>,
+}
+
+impl Planner {
+    /// The selected items that the target doesn't have, or has differently
+    fn select<T>(&mut self, names: &[String], source: &[T], target: &[T]) -> Vec<T>
+    where
+        T: ConfigItem + Clone + Serialize,
+    {
+        let mut selected = Vec::new();
+        for item in source
+            .iter()
+            .filter(|i| names.iter().any(|n| n == i.name()))
+        {
+            let (action, fields) = match target.iter().find(|t| t.name() == item.name()) {
+                Some(current) => {
+                    let fields = diff_fields(
+                        &normalize(&to_value(current), &self.target_names),
+                        &normalize(&to_value(item), &self.source_names),
+                    );
+                    if fields.is_empty() {
+                        self.unchanged.push((T::KIND, item.name().to_string()));
+                        continue;
+                    }
+                    (RestoreAction::Update, fields)
+                }
+                None => (RestoreAction::Create, Vec::new()),
+            };
+            self.changes.push(PromotedItem {
+                kind: T::KIND,
+                name: item.name().to_string(),
+                action,
+                fields,
+            });
+            selected.push(item.clone());
+        }
+        selected
+    }
+}
+
+fn endpoint_names(snapshot: &EnvironmentSnapshot) -> HashMap<String, String> {
+    snapshot
+        .sources
+        .iter()
+        .map(|s| (s.id().to_string(), s.name().to_string()))
+        .chain(
+            snapshot
+                .destinations
+                .iter()
+                .map(|d| (d.id().to_string(), d.name().to_string())),
+        )
+        .collect()
+}
+
+fn to_value<T: Serialize>(item: &T) -> Value {
+    serde_json::to_value(item).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::key::Key;
+
+    /// The same configuration in every environment, with IDs that start
+    /// with the environment's name so they never match between them
+    fn environment(env: &str) -> EnvironmentSnapshot {
+        serde_json::from_value(json!({
+            "sources": [{ "id": format!("{env}-src-ehr"), "name": "EHR" }],
+            "destinations": [{ "id": format!("{env}-dst-app"), "name": "Patient app" }],
+            "filters": [{
+                "id": format!("{env}-flt-test"),
+                "name": "Drop test patients",
+                "dataModel": "PatientAdmin",
+                "rules": { "field": "Patient.Demographics.LastName", "value": "TEST" },
+                "attachedTo": { "destinations": [format!("{env}-dst-app")] },
+            }],
+            "configModifiers": [{
+                "id": format!("{env}-cm-facility"),
+                "name": "Default facility",
+                "modifications": { "facility": "Main campus" },
+                "attachedTo": { "sources": [format!("{env}-src-ehr")] },
+            }],
+            "translationSets": [{
+                "id": format!("{env}-ts-sex"),
+                "name": "Administrative sex",
+                "mappings": [{ "sourceCode": "F", "targetCode": "female" }],
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn everything() -> PromotionItems {
+        PromotionItems {
+            filters: vec!["Drop test patients".into()],
+            config_modifiers: vec!["Default facility".into()],
+            translation_sets: vec!["Administrative sex".into()],
+        }
+    }
+
+    fn names(changes: &[PromotedItem]) -> Vec<(ConfigKind, &str, RestoreAction)> {
+        changes
+            .iter()
+            .map(|c| (c.kind, c.name.as_str(), c.action))
+            .collect()
+    }
+
+    #[test]
+    fn items_the_target_already_has_are_up_to_date() {
+        let promotion = Promotion {
+            source: environment("dev"),
+            target: environment("staging"),
+        };
+        let plan = promotion.plan(&everything());
+
+        // the attachments are to endpoints with different IDs but the same
+        // names, so they don't count as a change
+        assert!(plan.is_empty());
+        assert!(plan.missing_endpoints.is_empty());
+        assert_eq!(
+            plan.unchanged,
+            [
+                (ConfigKind::Filter, "Drop test patients".to_string()),
+                (ConfigKind::ConfigModifier, "Default facility".to_string()),
+                (ConfigKind::TranslationSet, "Administrative sex".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn remaps_attached_endpoints_by_name() {
+        let mut source = environment("dev");
+        source.filters[0].rules["value"] = json!("ZZTEST");
+        source.config_modifiers[0].modifications["facility"] = json!("North campus");
+        let promotion = Promotion {
+            source,
+            target: environment("staging"),
+        };
+        let plan = promotion.plan(&everything());
+
+        assert_eq!(
+            names(&plan.changes),
+            [
+                (
+                    ConfigKind::Filter,
+                    "Drop test patients",
+                    RestoreAction::Update
+                ),
+                (
+                    ConfigKind::ConfigModifier,
+                    "Default facility",
+                    RestoreAction::Update
+                ),
+            ]
+        );
+        let fields: Vec<_> = plan.changes[0]
+            .fields
+            .iter()
+            .map(|f| (f.path.as_str(), f.left.clone(), f.right.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            [("rules.value", Some(json!("TEST")), Some(json!("ZZTEST")))]
+        );
+        assert_eq!(
+            plan.endpoint_ids,
+            HashMap::from([
+                ("dev-dst-app".to_string(), "staging-dst-app".to_string()),
+                ("dev-src-ehr".to_string(), "staging-src-ehr".to_string()),
+            ])
+        );
+        assert!(plan.missing_endpoints.is_empty());
+    }
+
+    #[test]
+    fn only_plans_the_selected_items() {
+        let mut source = environment("dev");
+        source.filters[0].rules["value"] = json!("ZZTEST");
+        source.config_modifiers[0].name = "Fallback facility".into();
+        source.translation_sets[0].mappings.clear();
+        let promotion = Promotion {
+            source,
+            target: environment("staging"),
+        };
+        let selected = PromotionItems {
+            config_modifiers: vec!["Fallback facility".into()],
+            // not in the source, so ignored
+            translation_sets: vec!["Race".into()],
+            ..Default::default()
+        };
+        let plan = promotion.plan(&selected);
+
+        assert_eq!(
+            names(&plan.changes),
+            [(
+                ConfigKind::ConfigModifier,
+                "Fallback facility",
+                RestoreAction::Create
+            )]
+        );
+        assert!(plan.unchanged.is_empty());
+        assert!(plan.items.filters.is_empty() && plan.items.translation_sets.is_empty());
+        assert_eq!(plan.items.config_modifiers.len(), 1);
+        // only the selected modifier's endpoint needs remapping
+        assert_eq!(
+            plan.endpoint_ids,
+            HashMap::from([("dev-src-ehr".to_string(), "staging-src-ehr".to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_endpoints_block_apply() {
+        let mut target = environment("prod");
+        target.destinations.clear();
+        target.filters.clear();
+        let promotion = Promotion {
+            source: environment("dev"),
+            target,
+        };
+        let plan = promotion.plan(&everything());
+        assert_eq!(plan.missing_endpoints, ["Patient app"]);
+
+        // nothing listens here, so any request would fail differently
+        let client =
+            RedoxRequestClient::new("http://localhost:0", None, Key::default(), "kid", "client")
+                .unwrap();
+        let error = plan.apply(&client, 1, "env-prod").await.unwrap_err();
+        assert!(
+            matches!(&error.source, RedoxApiError::MissingEndpoints(names) if names == &["Patient app"]),
+            "{:?}",
+            error.source
+        );
+        assert!(error.restored.is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method, StatusCode,
+};
+
+/// How [crate::RedoxRequestClient] retries requests that fail for reasons
+/// that are likely to go away on their own: rate limiting, server errors and
+/// dropped connections.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Retries after the first attempt. Zero disables retrying entirely.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for every retry after that
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including one asked for by the
+    /// server through `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Never retry
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Exponential backoff with full jitter for the given retry (starting at
+    /// zero), so concurrent clients don't all come back at the same moment
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let millis = ceiling.as_millis() as u64;
+        Duration::from_millis(rand::random_range(millis / 2..=millis))
+    }
+
+    /// The delay before retrying a response with the given status, or `None`
+    /// if it shouldn't be retried. Rate limiting is always safe to retry, but
+    /// server errors are only retried for idempotent methods so that a create
+    /// that actually landed isn't repeated.
+    pub(crate) fn delay_for_response(
+        &self,
+        method: &Method,
+        status: StatusCode,
+        headers: &HeaderMap,
+        retry: u32,
+    ) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+
+        let retryable = status == StatusCode::TOO_MANY_REQUESTS
+            || (status.is_server_error() && is_idempotent(method));
+        if !retryable {
+            return None;
+        }
+
+        let delay = retry_after(headers)
+            .map(|d| d.min(self.max_delay))
+            .unwrap_or_else(|| self.backoff(retry));
+        Some(delay)
+    }
+
+    /// The delay before retrying a request that never got a response, or
+    /// `None` if it shouldn't be retried
+    pub(crate) fn delay_for_error(
+        &self,
+        method: &Method,
+        error: &reqwest::Error,
+        retry: u32,
+    ) -> Option<Duration> {
+        let retryable = error.is_connect() || (error.is_timeout() && is_idempotent(method));
+        (retry < self.max_retries && retryable).then(|| self.backoff(retry))
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+/// Parse the `Retry-After` header, which is either a number of seconds or an
+/// HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use chrono::TimeDelta;
+    use reqwest::{header::HeaderValue, Client};
+
+    use super::*;
+
+    fn config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+
+    fn retry_after_header(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retries_rate_limiting_for_every_method() {
+        let none = HeaderMap::new();
+        for method in [Method::GET, Method::POST, Method::PUT, Method::DELETE] {
+            let delay =
+                config().delay_for_response(&method, StatusCode::TOO_MANY_REQUESTS, &none, 0);
+            assert!(delay.is_some(), "{method} wasn't retried");
+        }
+    }
+
+    #[test]
+    fn retries_server_errors_only_for_idempotent_methods() {
+        let none = HeaderMap::new();
+        let retried = |method: Method, status| {
+            config()
+                .delay_for_response(&method, status, &none, 0)
+                .is_some()
+        };
+
+        assert!(retried(Method::GET, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(retried(Method::PUT, StatusCode::BAD_GATEWAY));
+        assert!(retried(Method::DELETE, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!retried(Method::POST, StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!retried(Method::PATCH, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!retried(Method::GET, StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn stops_after_max_retries() {
+        let none = HeaderMap::new();
+        let delay = config().delay_for_response(&Method::GET, StatusCode::BAD_GATEWAY, &none, 3);
+        assert_eq!(delay, None);
+    }
+
+    #[test]
+    fn backs_off_within_bounds() {
+        let config = config();
+        for retry in 0..3 {
+            let ceiling = config.base_delay * 2u32.pow(retry);
+            let delay = config.backoff(retry);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{delay:?}");
+        }
+        assert!(config.backoff(20) <= config.max_delay);
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let headers = retry_after_header("7");
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+        let delay =
+            config().delay_for_response(&Method::POST, StatusCode::TOO_MANY_REQUESTS, &headers, 0);
+        assert_eq!(delay, Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn parses_retry_after_dates() {
+        let soon = Utc::now() + TimeDelta::seconds(5);
+        // HTTP dates are always written in GMT, but numeric offsets turn up too
+        let http_date = soon.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        for date in [http_date, soon.to_rfc2822()] {
+            let delay = retry_after(&retry_after_header(&date)).unwrap();
+            assert!(delay > Duration::from_secs(3) && delay <= Duration::from_secs(5));
+        }
+
+        // a date that's already passed can't be waited for
+        let past = (Utc::now() - TimeDelta::seconds(5)).to_rfc2822();
+        assert_eq!(retry_after(&retry_after_header(&past)), None);
+        assert_eq!(retry_after(&retry_after_header("soon")), None);
+    }
+
+    #[test]
+    fn caps_retry_after_at_max_delay() {
+        let date = (Utc::now() + TimeDelta::hours(1)).to_rfc2822();
+        for value in ["3600", date.as_str()] {
+            let delay = config().delay_for_response(
+                &Method::GET,
+                StatusCode::SERVICE_UNAVAILABLE,
+                &retry_after_header(value),
+                0,
+            );
+            assert_eq!(delay, Some(config().max_delay), "{value}");
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_timeouts_only_for_idempotent_methods() {
+        // accepts connections but never answers, so every request times out
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        for (method, retried) in [(Method::GET, true), (Method::POST, false)] {
+            let error = client
+                .request(method.clone(), &url)
+                .send()
+                .await
+                .unwrap_err();
+            assert!(error.is_timeout());
+            let delay = config().delay_for_error(&method, &error, 0);
+            assert_eq!(delay.is_some(), retried, "{method}");
+        }
+    }
+}
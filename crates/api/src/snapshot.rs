@@ -0,0 +1,378 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use tracing::debug;
+
+use crate::{
+    models::{
+        config_modifier::{ConfigModifier, ConfigModifierBody, ConfigModifierResource},
+        destination::{Destination, DestinationBody, DestinationResource},
+        filter::{Filter, FilterBody, FilterResource},
+        source::{Source, SourceBody, SourceResource},
+        translation_set::{TranslationSet, TranslationSetBody, TranslationSetResource},
+        RedoxApiResource, RequestType,
+    },
+    RedoxApiError, RedoxRequestClient,
+};
+
+/// The kinds of configuration that make up an environment's setup, in the
+/// order they have to be restored so references between them can be remapped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display, Serialize)]
+pub enum ConfigKind {
+    Source,
+    Destination,
+    Filter,
+    #[strum(to_string = "Config modifier")]
+    ConfigModifier,
+    #[strum(to_string = "Translation set")]
+    TranslationSet,
+}
+
+/// A piece of configuration that lives in an environment. Items are matched
+/// across environments by name, since their IDs differ.
+pub trait ConfigItem {
+    const KIND: ConfigKind;
+
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+}
+
+macro_rules! config_item {
+    ($item:ty, $kind:expr) => {
+        impl ConfigItem for $item {
+            const KIND: ConfigKind = $kind;
+
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+        }
+    };
+}
+
+config_item!(Source, ConfigKind::Source);
+config_item!(Destination, ConfigKind::Destination);
+config_item!(Filter, ConfigKind::Filter);
+config_item!(ConfigModifier, ConfigKind::ConfigModifier);
+config_item!(TranslationSet, ConfigKind::TranslationSet);
+
+/// Everything needed to rebuild an environment's configuration. Data model
+/// subscriptions are part of the sources and destinations.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EnvironmentSnapshot {
+    #[serde(default)]
+    pub sources: Vec<Source>,
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+    #[serde(rename = "configModifiers", default)]
+    pub config_modifiers: Vec<ConfigModifier>,
+    #[serde(rename = "translationSets", default)]
+    pub translation_sets: Vec<TranslationSet>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum RestoreAction {
+    Create,
+    Update,
+}
+
+/// What restoring did, or would do, to one item
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoredItem {
+    pub kind: ConfigKind,
+    pub name: String,
+    pub action: RestoreAction,
+    /// The item's ID in the snapshot
+    #[serde(rename = "snapshotId")]
+    pub snapshot_id: String,
+    /// The item's ID in the target environment. A dry run doesn't know the
+    /// IDs of items it would create.
+    pub id: Option<String>,
+}
+
+impl EnvironmentSnapshot {
+    /// Fetch the configuration of an environment
+    pub async fn fetch(
+        client: &RedoxRequestClient,
+        org_id: i32,
+        environment_id: &str,
+    ) -> Result<Self, RedoxApiError> {
+        let sources = list(client, SourceResource::new(org_id, environment_id))
+            .await?
+            .map_or_else(Vec::new, |l| l.sources);
+        let destinations = list(client, DestinationResource::new(org_id, environment_id))
+            .await?
+            .map_or_else(Vec::new, |l| l.destinations);
+        let filters = list(client, FilterResource::new(org_id, environment_id))
+            .await?
+            .map_or_else(Vec::new, |l| l.filters);
+        let config_modifiers = list(client, ConfigModifierResource::new(org_id, environment_id))
+            .await?
+            .map_or_else(Vec::new, |l| l.config_modifiers);
+
+        let translation_sets = translation_sets(client, org_id, environment_id).await?;
+
+        debug!(
+            "fetched snapshot of environment {} in organization {}",
+            environment_id, org_id
+        );
+        Ok(Self {
+            sources,
+            destinations,
+            filters,
+            config_modifiers,
+            translation_sets,
+        })
+    }
+
+    /// How many items the snapshot holds
+    pub fn len(&self) -> usize {
+        self.sources.len()
+            + self.destinations.len()
+            + self.filters.len()
+            + self.config_modifiers.len()
+            + self.translation_sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Recreate the snapshot's configuration in an environment. Items are
+    /// matched to the environment's existing items by name: matches are
+    /// updated in place and everything else is created. Nothing is deleted.
+    ///
+    /// Sources and destinations go first, so the endpoints filters and config
+    /// modifiers are attached to can be remapped to their IDs in the target
+    /// environment. With `dry_run` nothing is changed, and the result is what
+    /// would have been done. The restore stops at the first item that can't
+    /// be saved, and the error has the items restored before it.
+    pub async fn restore(
+        &self,
+        client: &RedoxRequestClient,
+        org_id: i32,
+        environment_id: &str,
+        dry_run: bool,
+    ) -> Result<Vec<RestoredItem>, RestoreError> {
+        let target = Self::fetch(client, org_id, environment_id).await?;
+        self.restore_onto(
+            client,
+            org_id,
+            environment_id,
+            &target,
+            HashMap::new(),
+            dry_run,
+        )
+        .await
+    }
+
+    /// Restore into an environment whose configuration has already been
+    /// fetched. `ids` maps the IDs of endpoints that aren't in the snapshot
+    /// to their IDs in the target, so items attached to them can be remapped.
+    pub(crate) async fn restore_onto(
+        &self,
+        client: &RedoxRequestClient,
+        org_id: i32,
+        environment_id: &str,
+        target: &Self,
+        ids: HashMap<String, String>,
+        dry_run: bool,
+    ) -> Result<Vec<RestoredItem>, RestoreError> {
+        let mut restorer = Restorer {
+            client,
+            dry_run,
+            ids,
+            restored: Vec::new(),
+        };
+
+        for source in &self.sources {
+            let resource = SourceResource::new(org_id, environment_id);
+            let body = SourceBody::from(source.clone());
+            restorer
+                .upsert(resource, &target.sources, source, body)
+                .await?;
+        }
+        for destination in &self.destinations {
+            let resource = DestinationResource::new(org_id, environment_id);
+            let body = DestinationBody::from(destination.clone());
+            restorer
+                .upsert(resource, &target.destinations, destination, body)
+                .await?;
+        }
+        for filter in &self.filters {
+            let resource = FilterResource::new(org_id, environment_id);
+            let mut body = FilterBody::from(filter.clone());
+            body.attached_to = body.attached_to.remapped(&restorer.ids);
+            restorer
+                .upsert(resource, &target.filters, filter, body)
+                .await?;
+        }
+        for modifier in &self.config_modifiers {
+            let resource = ConfigModifierResource::new(org_id, environment_id);
+            let mut body = ConfigModifierBody::from(modifier.clone());
+            body.attached_to = body.attached_to.remapped(&restorer.ids);
+            restorer
+                .upsert(resource, &target.config_modifiers, modifier, body)
+                .await?;
+        }
+        for set in &self.translation_sets {
+            let resource = TranslationSetResource::new(org_id, environment_id);
+            let body = TranslationSetBody::from(set.clone());
+            restorer
+                .upsert(resource, &target.translation_sets, set, body)
+                .await?;
+        }
+
+        Ok(restorer.restored)
+    }
+}
+
+/// Keeps track of a restore in progress
+struct Restorer<'a> {
+    client: &'a RedoxRequestClient,
+    dry_run: bool,
+    /// Snapshot IDs mapped to the IDs of the same items in the target
+    ids: HashMap<String, String>,
+    restored: Vec<RestoredItem>,
+}
+
+impl Restorer<'_> {
+    /// Update the existing item with the same name, or create one
+    async fn upsert<R>(
+        &mut self,
+        resource: R,
+        existing: &[R::Item],
+        item: &R::Item,
+        body: R::Body,
+    ) -> Result<(), RestoreError>
+    where
+        R: RedoxApiResource + Debug,
+        R::Item: ConfigItem,
+    {
+        let current = existing.iter().find(|e| e.name() == item.name());
+        let (action, mut id) = match current {
+            Some(current) => (RestoreAction::Update, Some(current.id().to_string())),
+            None => (RestoreAction::Create, None),
+        };
+
+        if !self.dry_run {
+            let request = match &id {
+                Some(id) => RequestType::Update(id.clone(), body),
+                None => RequestType::Create(body),
+            };
+            let saved = match self.client.make_request(request, resource).await {
+                Ok(saved) => saved,
+                Err(source) => {
+                    return Err(RestoreError {
+                        failed: Some((R::Item::KIND, item.name().to_string())),
+                        restored: std::mem::take(&mut self.restored),
+                        source,
+                    })
+                }
+            };
+            if let Some(saved) = saved.into_single() {
+                id = Some(saved.id().to_string());
+            }
+            debug!("{} {} {}", action, R::Item::KIND, item.name());
+        }
+
+        if let Some(id) = &id {
+            self.ids.insert(item.id().to_string(), id.clone());
+        }
+        self.restored.push(RestoredItem {
+            kind: R::Item::KIND,
+            name: item.name().to_string(),
+            action,
+            snapshot_id: item.id().to_string(),
+            id,
+        });
+        Ok(())
+    }
+}
+
+/// A restore that stopped partway. Items are restored one at a time and
+/// nothing is rolled back, so `restored` is what had already been created or
+/// updated when it failed.
+#[derive(Debug)]
+pub struct RestoreError {
+    /// The item that couldn't be saved, if the restore got as far as saving
+    pub failed: Option<(ConfigKind, String)>,
+    pub restored: Vec<RestoredItem>,
+    pub source: RedoxApiError,
+}
+
+impl From<RedoxApiError> for RestoreError {
+    fn from(source: RedoxApiError) -> Self {
+        Self {
+            failed: None,
+            restored: Vec::new(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.failed {
+            Some((kind, name)) => write!(
+                f,
+                "Failed to restore {kind} {name} after restoring {} items",
+                self.restored.len()
+            ),
+            None => write!(f, "Failed to restore the environment"),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Every translation set in an environment, including its mappings. The list
+/// leaves those out, so the sets are fetched one by one.
+pub(crate) async fn translation_sets(
+    client: &RedoxRequestClient,
+    org_id: i32,
+    environment_id: &str,
+) -> Result<Vec<TranslationSet>, RedoxApiError> {
+    let resource = TranslationSetResource::new(org_id, environment_id);
+    let mut translation_sets = Vec::new();
+    for set in list(client, resource.clone())
+        .await?
+        .map_or_else(Vec::new, |l| l.translation_sets)
+    {
+        let full = client
+            .make_request(RequestType::Get(set.id.clone()), resource.clone())
+            .await?
+            .into_single()
+            .unwrap_or(set);
+        translation_sets.push(full);
+    }
+    Ok(translation_sets)
+}
+
+pub(crate) async fn list<R>(
+    client: &RedoxRequestClient,
+    resource: R,
+) -> Result<Option<R::List>, RedoxApiError>
+where
+    R: RedoxApiResource + Debug,
+{
+    Ok(client
+        .make_request(RequestType::List, resource)
+        .await?
+        .into_list())
+}
@@ -0,0 +1,202 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::Jwt;
+
+/// Keeps the most recent JWT on disk, so back to back runs of the tool can
+/// reuse a token instead of asking the auth host for a new one every time.
+/// Failing to read or write the cache is never fatal; we just fall back to
+/// fetching a token.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenCache {
+    path: PathBuf,
+    /// The auth host tokens are issued by. The cache file is named after the
+    /// deployment, so this catches a deployment being pointed somewhere else.
+    host: String,
+}
+
+/// What's written to the cache file
+#[derive(Deserialize, Serialize)]
+struct CachedJwt {
+    /// Missing from caches written before the host was recorded, which are
+    /// then treated as being from another host
+    #[serde(default)]
+    host: String,
+    #[serde(flatten)]
+    jwt: Jwt,
+}
+
+impl TokenCache {
+    pub fn new(path: PathBuf, host: &str) -> Self {
+        Self {
+            path,
+            host: host.to_string(),
+        }
+    }
+
+    /// Load the cached token, if there is one from the same auth host that
+    /// hasn't expired
+    pub fn load(&self) -> Option<Jwt> {
+        let data = fs::read_to_string(&self.path).ok()?;
+        let cached = serde_json::from_str::<CachedJwt>(&data)
+            .map_err(|e| {
+                warn!(
+                    "ignoring unreadable token cache {:?}. Error: {}",
+                    self.path, e
+                )
+            })
+            .ok()?;
+
+        if cached.host != self.host {
+            debug!("cached jwt is from {}, not {}", cached.host, self.host);
+            return None;
+        }
+        let jwt = cached.jwt;
+        if jwt.expires_at < Utc::now().timestamp() {
+            debug!("cached jwt has expired");
+            return None;
+        }
+        debug!("loaded jwt from {:?}", self.path);
+        Some(jwt)
+    }
+
+    /// Write the token to disk, readable only by the current user
+    pub fn store(&self, jwt: &Jwt) {
+        if let Err(e) = self.try_store(jwt) {
+            warn!("failed to cache jwt at {:?}. Error: {}", self.path, e);
+        }
+    }
+
+    fn try_store(&self, jwt: &Jwt) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a sibling file and rename it into place, so a concurrent
+        // run never reads a half written token
+        let temp_path = self.path.with_extension("tmp");
+        let mut file = private_file(&temp_path)?;
+        let cached = CachedJwt {
+            host: self.host.clone(),
+            jwt: jwt.clone(),
+        };
+        file.write_all(serde_json::to_string(&cached)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &self.path)
+    }
+}
+
+#[cfg(unix)]
+fn private_file(path: &Path) -> std::io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // mode only applies when the file is created, so tighten up any leftover
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn private_file(path: &Path) -> std::io::Result<fs::File> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "https://api.redoxengine.com";
+
+    fn jwt(expires_in: i64) -> Jwt {
+        Jwt {
+            token: "token".into(),
+            expires_at: Utc::now().timestamp() + expires_in,
+        }
+    }
+
+    #[test]
+    fn reuses_a_stored_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TokenCache::new(dir.path().join("tokens").join("dev.json"), HOST);
+        assert!(cache.load().is_none());
+
+        cache.store(&jwt(600));
+        assert_eq!(cache.load().map(|jwt| jwt.token), Some("token".into()));
+    }
+
+    #[test]
+    fn ignores_expired_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = TokenCache::new(dir.path().join("dev.json"), HOST);
+        cache.store(&jwt(-1));
+        assert!(cache.load().is_none());
+    }
+
+    #[test]
+    fn ignores_tokens_from_another_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev.json");
+        TokenCache::new(path.clone(), "https://old.example.com").store(&jwt(600));
+        assert!(TokenCache::new(path, HOST).load().is_none());
+    }
+
+    #[test]
+    fn ignores_corrupt_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev.json");
+        let cache = TokenCache::new(path.clone(), HOST);
+        for data in [
+            "{\"host\": ",
+            "[]",
+            "{\"host\": \"https://api.redoxengine.com\"}",
+        ] {
+            fs::write(&path, data).unwrap();
+            assert!(cache.load().is_none(), "{data}");
+        }
+
+        // and replaces them with the next token
+        cache.store(&jwt(600));
+        assert!(cache.load().is_some());
+    }
+
+    #[test]
+    fn ignores_tokens_cached_without_a_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev.json");
+        let expires_at = Utc::now().timestamp() + 600;
+        fs::write(
+            &path,
+            format!("{{\"token\": \"token\", \"expires_at\": {expires_at}}}"),
+        )
+        .unwrap();
+        assert!(TokenCache::new(path, HOST).load().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stores_privately() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev.json");
+        TokenCache::new(path.clone(), HOST).store(&jwt(600));
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,59 @@
+//! Replay requests from a cassette checked in next to the tests, the way a
+//! bug recorded against a real deployment would be reproduced
+
+use std::path::PathBuf;
+
+use redox_api::{
+    key::Key,
+    models::{environment::EnvironmentResource, source::SourceResource, RequestType},
+    Cassette, RedoxApiError, RedoxRequestClient,
+};
+
+/// A client that answers every request from the named cassette. It has no
+/// key or reachable host, so anything not in the cassette fails.
+fn replay_client(name: &str) -> RedoxRequestClient {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/cassettes")
+        .join(name);
+    let cassette = Cassette::replay(path).unwrap();
+    RedoxRequestClient::new("http://localhost:0", None, Key::default(), "kid", "client")
+        .unwrap()
+        .with_cassette(cassette)
+}
+
+#[tokio::test]
+async fn serves_requests_from_the_cassette() {
+    let client = replay_client("environments.json");
+
+    let environments = client
+        .make_request(RequestType::List, EnvironmentResource::new(1))
+        .await
+        .unwrap()
+        .into_list()
+        .unwrap()
+        .environments;
+    let names: Vec<&str> = environments.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["Development", "Staging", "Production"]);
+
+    let source = client
+        .make_request(
+            RequestType::Get("src-dev-ehr".to_string()),
+            SourceResource::new(1, "env-dev"),
+        )
+        .await
+        .unwrap()
+        .into_single()
+        .unwrap();
+    assert_eq!(source.name, "EHR");
+    // Recorded with the token redacted
+    assert_eq!(source.verification_token.as_deref(), Some("[REDACTED]"));
+}
+
+#[tokio::test]
+async fn fails_requests_missing_from_the_cassette() {
+    let client = replay_client("environments.json");
+    let result = client
+        .make_request(RequestType::List, EnvironmentResource::new(2))
+        .await;
+    assert!(matches!(result, Err(RedoxApiError::Cassette(_))));
+}
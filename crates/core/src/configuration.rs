@@ -48,7 +48,7 @@ impl ConfigurationFile {
     /// [Self::try_path] to find the file themself. This pattern enables the
     /// TUI to start up and watch the collection file, even if it's invalid.
     pub async fn load(path: PathBuf) -> anyhow::Result<Self> {
-        let configuration = load_configuration(path.clone()).await?.into();
+        let configuration = load_configuration(path.clone()).await?;
         Ok(Self {
             path,
             configuration,
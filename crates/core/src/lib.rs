@@ -1,5 +1,7 @@
 mod configuration;
+mod selection;
 
 pub mod util;
 
-pub use configuration::{Configuration, ConfigurationFile, Deployment, DeploymentAuth};
+pub use configuration::{Configuration, ConfigurationFile, Deployment, DeploymentAuth, KeySource};
+pub use selection::Selection;
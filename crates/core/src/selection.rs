@@ -0,0 +1,57 @@
+use anyhow::anyhow;
+use std::path::PathBuf;
+
+use crate::{Configuration, ConfigurationFile, Deployment};
+
+/// What to connect to, as picked on the command line or through the `RC_*`
+/// environment variables. Anything left unset falls back to the defaults in
+/// the configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Configuration file to load instead of searching for one
+    pub config: Option<PathBuf>,
+    /// Name of the deployment to use instead of the default one
+    pub deployment: Option<String>,
+    /// Organization to use instead of the deployment's default
+    pub org: Option<i32>,
+    /// ID or name of the environment to use
+    pub env: Option<String>,
+}
+
+impl Selection {
+    /// The configuration file to load: the one given, or else the one found
+    /// in the current or an ancestor directory
+    pub fn config_path(&self) -> anyhow::Result<PathBuf> {
+        match &self.config {
+            Some(path) => Ok(path.clone()),
+            None => ConfigurationFile::try_path(None, None),
+        }
+    }
+
+    /// The deployment to use: the one named, or else the default one if the
+    /// configuration has one. Naming a deployment that doesn't exist is an
+    /// error rather than a quiet fallback to the default.
+    pub fn deployment<'a>(
+        &self,
+        configuration: &'a Configuration,
+    ) -> anyhow::Result<Option<&'a Deployment>> {
+        match &self.deployment {
+            Some(name) => configuration
+                .deployments
+                .iter()
+                .find(|d| &d.name == name)
+                .map(Some)
+                .ok_or_else(|| anyhow!("No deployment named {name} in the configuration")),
+            None => Ok(configuration
+                .deployments
+                .iter()
+                .find(|d| d.default == Some(true))),
+        }
+    }
+
+    /// The organization to use with the deployment, if one was picked or the
+    /// deployment has a default
+    pub fn org(&self, deployment: &Deployment) -> Option<i32> {
+        self.org.or(deployment.default_org)
+    }
+}
@@ -1,5 +1,11 @@
-use std::{io::Read, ops::Deref};
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    ops::Deref,
+    path::Path,
+};
 
+use anyhow::Context;
 use serde::de::DeserializeOwned;
 use tracing::error;
 
@@ -13,6 +19,52 @@ pub fn parse_yaml<T: DeserializeOwned>(reader: impl Read) -> serde_yaml::Result<
     serde_yaml::from_value(yaml_value)
 }
 
+/// Write a file that only the current user can read, such as a private key.
+/// Refuses to replace an existing file unless `overwrite` is set.
+pub fn write_private_file(path: &Path, contents: &str, overwrite: bool) -> anyhow::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Error creating file {path:?}"))?;
+    #[cfg(unix)]
+    {
+        // mode only applies to newly created files
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("Error writing file {path:?}"))?;
+    Ok(())
+}
+
+/// Create a directory and any missing parents that only the current user can
+/// read, for files written with [write_private_file]. Directories that
+/// already exist are left as they are.
+pub fn create_private_dir(path: &Path) -> anyhow::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(path)
+        .with_context(|| format!("Error creating {path:?}"))
+}
+
 /// Extension trait for [Result]
 pub trait ResultTraced<T, E>: Sized {
     /// If this is an error, trace it. Return the same result.
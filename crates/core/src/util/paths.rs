@@ -4,9 +4,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-/// Get the path of the directory to contain log files. **Directory
-/// may not exist yet**, caller must create it.
-pub fn log_directory() -> PathBuf {
+/// Get the path of the directory to contain state that should persist
+/// between runs, such as logs and cached tokens. **Directory may not exist
+/// yet**, caller must create it.
+pub fn state_directory() -> PathBuf {
     // State dir is only present on windows, but cache dir will be present on
     // all platforms
     // https://docs.rs/dirs/latest/dirs/fn.state_dir.html
@@ -18,6 +19,12 @@ pub fn log_directory() -> PathBuf {
     )
 }
 
+/// Get the path of the directory to contain log files. **Directory
+/// may not exist yet**, caller must create it.
+pub fn log_directory() -> PathBuf {
+    state_directory()
+}
+
 /// Get the path to the primary log file. **Parent direct may not exist yet,**
 /// caller must create it.
 pub fn log_file() -> PathBuf {
@@ -30,6 +37,27 @@ pub fn log_file_old() -> PathBuf {
     log_directory().join("redox_commander.log.old")
 }
 
+/// Get the path to the cached JWT for a deployment and client ID. **Parent
+/// directory may not exist yet,** caller must create it.
+pub fn token_cache_file(deployment: &str, client_id: &str) -> PathBuf {
+    let sanitize = |s: &str| {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>()
+    };
+    state_directory().join("tokens").join(format!(
+        "{}.{}.json",
+        sanitize(deployment),
+        sanitize(client_id)
+    ))
+}
+
 /// In debug mode, use a local directory for all files. In release, use the
 /// given path.
 fn debug_or(path: PathBuf) -> PathBuf {
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{auth::is_authorized, store::Page, MockState};
+
+/// The API version reported in every response's meta
+const VERSION: &str = "v1";
+
+/// Serve any API path from the store: GET lists a collection or fetches an
+/// item, POST creates, PUT replaces, PATCH merges and DELETE removes
+pub async fn handle(
+    State(state): State<Arc<MockState>>,
+    method: Method,
+    uri: Uri,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !is_authorized(&state, &headers) {
+        return error(StatusCode::UNAUTHORIZED, "Missing or expired access token");
+    }
+
+    let path = uri.path().trim_matches('/');
+    debug!("{} {}", method, path);
+
+    let body = if body.is_empty() {
+        None
+    } else {
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(body) => Some(body),
+            Err(e) => return error(StatusCode::BAD_REQUEST, &format!("Invalid JSON body: {e}")),
+        }
+    };
+
+    let is_write = matches!(method, Method::POST | Method::PUT | Method::PATCH);
+    if is_write
+        && state
+            .options
+            .reject_writes
+            .iter()
+            .any(|rejected| path.starts_with(rejected.trim_matches('/')))
+    {
+        return error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Writes to {path} are rejected"),
+        );
+    }
+
+    match method {
+        Method::GET => {
+            let store = state.store.read().unwrap();
+            if let Some(page) = store.list(path, &query, state.options.page_size) {
+                return list(page, state.options.page_size);
+            }
+            match store.get(path) {
+                Some(item) => single(StatusCode::OK, item),
+                None => not_found(path),
+            }
+        }
+        Method::POST => {
+            let Some(body) = body else {
+                return error(StatusCode::BAD_REQUEST, "A request body is required");
+            };
+            match state.store.write().unwrap().create(path, body) {
+                Some(item) => single(StatusCode::CREATED, item),
+                None => error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Can't create an item at {path}"),
+                ),
+            }
+        }
+        Method::PUT | Method::PATCH => {
+            let Some(body) = body else {
+                return error(StatusCode::BAD_REQUEST, "A request body is required");
+            };
+            let merge = method == Method::PATCH;
+            match state.store.write().unwrap().update(path, body, merge) {
+                Some(item) => single(StatusCode::OK, item),
+                None => not_found(path),
+            }
+        }
+        Method::DELETE => {
+            if state.store.write().unwrap().delete(path) {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                not_found(path)
+            }
+        }
+        _ => error(
+            StatusCode::METHOD_NOT_ALLOWED,
+            &format!("{method} is not supported"),
+        ),
+    }
+}
+
+fn single(status: StatusCode, payload: Value) -> Response {
+    let envelope = json!({
+        "meta": { "version": VERSION },
+        "payload": payload,
+    });
+    (status, Json(envelope)).into_response()
+}
+
+/// A list response, with page meta when lists are being split into pages
+fn list(page: Page, page_size: Option<usize>) -> Response {
+    let mut meta = json!({ "version": VERSION });
+    if let Some(size) = page_size {
+        meta["page"] = json!({ "nextCursor": page.next_cursor, "size": size });
+    }
+    Json(json!({ "meta": meta, "payload": page.payload })).into_response()
+}
+
+fn not_found(path: &str) -> Response {
+    error(StatusCode::NOT_FOUND, &format!("Nothing found at {path}"))
+}
+
+/// An error in the shape the API sends them
+fn error(status: StatusCode, message: &str) -> Response {
+    let body = json!({
+        "message": message,
+        "errors": [{ "code": status.as_u16().to_string(), "message": message }],
+    });
+    (status, Json(body)).into_response()
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::HashSet,
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use chrono::Utc;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use redox_api::{key::Jwk, models::auth_credential::AuthCredential};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::MockState;
+
+const CLIENT_CREDENTIALS: &str = "client_credentials";
+const JWT_BEARER: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// Redox refuses client assertions that are valid for longer than this, in
+/// seconds
+const MAX_ASSERTION_LIFETIME: i64 = 5 * 60;
+
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    grant_type: Option<String>,
+    client_assertion_type: Option<String>,
+    client_assertion: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: i64,
+    scope: String,
+}
+
+#[derive(Deserialize)]
+struct AssertionClaims {
+    iss: String,
+    exp: i64,
+}
+
+/// Exchange a client assertion for an access token. The assertion has to be
+/// signed by a key in one of the fixtures' auth credentials, with that
+/// credential's client ID as its issuer and subject.
+pub async fn token(
+    State(state): State<Arc<MockState>>,
+    Form(request): Form<TokenRequest>,
+) -> Response {
+    if request.grant_type.as_deref() != Some(CLIENT_CREDENTIALS) {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "grant_type must be client_credentials",
+        );
+    }
+    if request.client_assertion_type.as_deref() != Some(JWT_BEARER) {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            &format!("client_assertion_type must be {JWT_BEARER}"),
+        );
+    }
+    let Some(assertion) = request.client_assertion else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "client_assertion is required",
+        );
+    };
+
+    let client_id = match verify_assertion(&state, &assertion) {
+        Ok(client_id) => client_id,
+        Err(reason) => {
+            warn!("rejected client assertion: {}", reason);
+            return oauth_error(StatusCode::UNAUTHORIZED, "invalid_client", &reason);
+        }
+    };
+
+    let access_token = format!(
+        "{:016x}{:016x}",
+        rand::random::<u64>(),
+        rand::random::<u64>()
+    );
+    let expires_in = state.options.token_ttl;
+    state
+        .tokens
+        .lock()
+        .unwrap()
+        .insert(access_token.clone(), Utc::now().timestamp() + expires_in);
+    state.tokens_issued.fetch_add(1, Ordering::SeqCst);
+    debug!(client_id, "issued access token");
+
+    Json(TokenResponse {
+        access_token,
+        token_type: "bearer",
+        expires_in,
+        scope: String::new(),
+    })
+    .into_response()
+}
+
+/// Check a client assertion against the fixtures' credentials, returning the
+/// client ID it was issued by or why it was rejected
+fn verify_assertion(state: &MockState, assertion: &str) -> Result<String, String> {
+    let header = decode_header(assertion).map_err(|e| format!("Malformed assertion: {e}"))?;
+    let kid = header
+        .kid
+        .ok_or_else(|| "The assertion header has no kid".to_string())?;
+
+    let (credential, jwk) = {
+        let store = state.store.read().unwrap();
+        let found = store
+            .items_named("authCredentials")
+            .filter_map(|item| serde_json::from_value::<AuthCredential>(item.clone()).ok())
+            .find_map(|credential| {
+                let jwk = credential
+                    .public_keys
+                    .keys
+                    .iter()
+                    .find(|k| k.kid == kid)
+                    .cloned()?;
+                Some((credential, jwk))
+            })
+            .ok_or_else(|| format!("No credential has a key with kid {kid}"))?;
+        found
+    };
+    if credential.is_expired() {
+        return Err(format!("Credential {} has expired", credential.id));
+    }
+
+    let algorithm = Algorithm::from_str(&jwk.alg)
+        .map_err(|_| format!("Key {kid} has an unknown algorithm {}", jwk.alg))?;
+    if header.alg != algorithm {
+        return Err(format!(
+            "Key {kid} is for {:?} but the assertion was signed with {:?}",
+            algorithm, header.alg
+        ));
+    }
+
+    let mut validation = Validation::new(algorithm);
+    validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+    validation.iss = Some(HashSet::from([credential.client_id.clone()]));
+    validation.sub = Some(credential.client_id.clone());
+
+    let claims = decode::<AssertionClaims>(assertion, &decoding_key(&jwk)?, &validation)
+        .map_err(|e| format!("Invalid assertion: {e}"))?
+        .claims;
+    if claims.exp - Utc::now().timestamp() > MAX_ASSERTION_LIFETIME {
+        return Err(format!(
+            "Assertions may be valid for at most {MAX_ASSERTION_LIFETIME} seconds"
+        ));
+    }
+
+    Ok(claims.iss)
+}
+
+fn decoding_key(jwk: &Jwk) -> Result<DecodingKey, String> {
+    let missing = |member| format!("Key {} has no {member}", jwk.kid);
+    let key = match jwk.kty.as_str() {
+        "RSA" => DecodingKey::from_rsa_components(
+            jwk.n.as_deref().ok_or_else(|| missing("n"))?,
+            jwk.e.as_deref().ok_or_else(|| missing("e"))?,
+        ),
+        "EC" => DecodingKey::from_ec_components(
+            jwk.x.as_deref().ok_or_else(|| missing("x"))?,
+            jwk.y.as_deref().ok_or_else(|| missing("y"))?,
+        ),
+        kty => return Err(format!("Key {} has an unsupported type {kty}", jwk.kid)),
+    };
+    key.map_err(|e| format!("Key {} is invalid: {e}", jwk.kid))
+}
+
+/// Whether the request carries a bearer token we issued that hasn't expired
+pub fn is_authorized(state: &MockState, headers: &HeaderMap) -> bool {
+    let Some(token) = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    let mut tokens = state.tokens.lock().unwrap();
+    let now = Utc::now().timestamp();
+    tokens.retain(|_, expires_at| *expires_at > now);
+    tokens.contains_key(token)
+}
+
+/// An error in the shape the auth host sends them
+fn oauth_error(status: StatusCode, error: &str, description: &str) -> Response {
+    (
+        status,
+        Json(json!({ "error": error, "error_description": description })),
+    )
+        .into_response()
+}
@@ -0,0 +1,133 @@
+//! A stand-in for the Redox API, for running the app and tests without live
+//! credentials. Tokens are issued by `/v2/auth/token` for client assertions
+//! signed with a key from one of the fixtures' auth credentials, and every
+//! other path is served from the fixture [Store].
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+use axum::{routing::post, Router};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tracing::info;
+
+mod api;
+mod auth;
+pub mod store;
+
+pub use store::Store;
+
+/// How long issued access tokens are valid for, in seconds, unless configured
+/// otherwise
+pub const DEFAULT_TOKEN_TTL: i64 = 3600;
+
+#[derive(Debug, Clone)]
+pub struct MockOptions {
+    /// Split lists into pages of this many items, to exercise pagination
+    pub page_size: Option<usize>,
+    /// Lifetime of issued access tokens, in seconds
+    pub token_ttl: i64,
+    /// Refuse to create or update items under these paths, to exercise how
+    /// clients handle a write failing partway through
+    pub reject_writes: Vec<String>,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        Self {
+            page_size: None,
+            token_ttl: DEFAULT_TOKEN_TTL,
+            reject_writes: Vec::new(),
+        }
+    }
+}
+
+/// Everything the handlers share
+struct MockState {
+    store: RwLock<Store>,
+    /// Issued access tokens and when they expire
+    tokens: Mutex<HashMap<String, i64>>,
+    /// How many access tokens have been issued
+    tokens_issued: AtomicUsize,
+    options: MockOptions,
+}
+
+impl MockState {
+    fn new(store: Store, options: MockOptions) -> Arc<Self> {
+        Arc::new(Self {
+            store: RwLock::new(store),
+            tokens: Mutex::new(HashMap::new()),
+            tokens_issued: AtomicUsize::new(0),
+            options,
+        })
+    }
+}
+
+/// Build the mock API's routes around a loaded store
+pub fn router(store: Store, options: MockOptions) -> Router {
+    routes(MockState::new(store, options))
+}
+
+fn routes(state: Arc<MockState>) -> Router {
+    Router::new()
+        .route("/v2/auth/token", post(auth::token))
+        .fallback(api::handle)
+        .with_state(state)
+}
+
+/// A mock server running in the background, for tests that want a real HTTP
+/// server to point a client at. It stops when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+    state: Arc<MockState>,
+}
+
+impl MockServer {
+    /// Load the fixtures and serve them on a random local port
+    pub async fn start(fixtures: &Path, options: MockOptions) -> anyhow::Result<Self> {
+        let store = Store::load(fixtures)?;
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        info!(%addr, "Starting mock server");
+
+        let state = MockState::new(store, options);
+        let app = routes(state.clone());
+        let handle = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        Ok(Self {
+            addr,
+            handle,
+            state,
+        })
+    }
+
+    /// The base URL to use as both the API and auth host
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// How many access tokens `/v2/auth/token` has issued so far
+    pub fn tokens_issued(&self) -> usize {
+        self.state.tokens_issued.load(Ordering::SeqCst)
+    }
+
+    /// Expire every access token issued so far, as if they'd timed out or
+    /// been revoked, while clients still think they're good
+    pub fn expire_tokens(&self) {
+        self.state.tokens.lock().unwrap().clear();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -0,0 +1,56 @@
+#![forbid(unsafe_code)]
+#![deny(clippy::all)]
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::Parser;
+use redox_mock_server::{router, MockOptions, Store, DEFAULT_TOKEN_TTL};
+use tokio::net::TcpListener;
+use tracing::info;
+use tracing_subscriber::{filter::LevelFilter, fmt};
+
+/// A local stand-in for the Redox API, serving fixture files. Point a
+/// deployment's apiHost at it and authenticate with a key from one of the
+/// fixtures' auth credentials.
+#[derive(Debug, Parser)]
+#[clap(author, version, about, name = "rc-mock")]
+struct Args {
+    /// Directory of JSON fixtures, laid out like the API's paths
+    #[clap(
+        long,
+        env = "RC_MOCK_FIXTURES",
+        default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")
+    )]
+    fixtures: PathBuf,
+    /// Address to listen on
+    #[clap(long, env = "RC_MOCK_ADDR", default_value = "127.0.0.1:8787")]
+    addr: SocketAddr,
+    /// Split lists into pages of this many items
+    #[clap(long)]
+    page_size: Option<usize>,
+    /// Lifetime of issued access tokens, in seconds
+    #[clap(long, default_value_t = DEFAULT_TOKEN_TTL)]
+    token_ttl: i64,
+    /// Refuse to create or update items under these paths, e.g.
+    /// platform/v1/organizations/1/environments/env-prod/filters
+    #[clap(long, value_delimiter = ',')]
+    reject_writes: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    fmt().with_max_level(LevelFilter::DEBUG).init();
+    let args = Args::parse();
+
+    let store = Store::load(&args.fixtures)?;
+    let options = MockOptions {
+        page_size: args.page_size,
+        token_ttl: args.token_ttl,
+        reject_writes: args.reject_writes,
+    };
+
+    let listener = TcpListener::bind(args.addr).await?;
+    info!("Serving {:?} on http://{}", args.fixtures, args.addr);
+    axum::serve(listener, router(store, options)).await?;
+    Ok(())
+}
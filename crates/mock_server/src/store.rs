@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+
+/// The API's data, loaded from fixture files and then changed in memory by
+/// create, update and delete requests. Nothing is written back to disk, so
+/// every run starts from the fixtures again.
+///
+/// A fixture's path relative to the fixture directory, minus `.json`, is the
+/// API path it's served at, and its contents are the response payload. A
+/// payload with a single array field, like `{"environments": [...]}`, is a
+/// collection: its items can also be fetched, updated and deleted at
+/// `<path>/<id>`, and new items are created by posting to the path.
+#[derive(Debug, Default)]
+pub struct Store {
+    documents: HashMap<String, Document>,
+}
+
+#[derive(Debug)]
+enum Document {
+    Collection { field: String, items: Vec<Value> },
+    Single(Value),
+}
+
+/// A page of a collection, along with the cursor for the following page
+pub struct Page {
+    pub payload: Value,
+    pub next_cursor: Option<String>,
+}
+
+impl Store {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut store = Self::default();
+        for file in json_files(dir)? {
+            let data = fs::read_to_string(&file)
+                .with_context(|| format!("Error reading fixture {file:?}"))?;
+            let payload: Value = serde_json::from_str(&data)
+                .with_context(|| format!("Error parsing fixture {file:?}"))?;
+            let path = file
+                .strip_prefix(dir)?
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            store
+                .documents
+                .insert(path, Document::from_payload(payload));
+        }
+        Ok(store)
+    }
+
+    /// Every item of every collection with the given field name, e.g. all
+    /// `authCredentials` across environments
+    pub fn items_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Value> {
+        self.documents
+            .values()
+            .flat_map(move |document| match document {
+                Document::Collection { field, items } if field == name => items.as_slice(),
+                _ => &[],
+            })
+    }
+
+    /// A page of the collection at the path, or the document there. Query
+    /// parameters that name a string field of the items filter on it.
+    ///
+    /// A path with no fixture that ends in the name of a collection found
+    /// elsewhere, e.g. the `sources` of an environment that has none, is an
+    /// empty collection, like it would be in the API.
+    pub fn list(
+        &self,
+        path: &str,
+        query: &HashMap<String, String>,
+        page_size: Option<usize>,
+    ) -> Option<Page> {
+        let (field, items) = match self.documents.get(path) {
+            Some(Document::Single(payload)) => {
+                return Some(Page {
+                    payload: payload.clone(),
+                    next_cursor: None,
+                })
+            }
+            Some(Document::Collection { field, items }) => (field, items),
+            None => return self.empty_collection(path),
+        };
+
+        let matching: Vec<&Value> = items
+            .iter()
+            .filter(|item| {
+                query.iter().all(|(name, value)| match item.get(name) {
+                    Some(Value::String(field)) => field.eq_ignore_ascii_case(value),
+                    _ => true,
+                })
+            })
+            .collect();
+
+        let offset: usize = query
+            .get("cursor")
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(0);
+        let size = page_size.unwrap_or(matching.len()).max(1);
+        let page: Vec<Value> = matching
+            .iter()
+            .skip(offset)
+            .take(size)
+            .map(|item| (*item).clone())
+            .collect();
+        let next_cursor = (offset + size < matching.len()).then(|| (offset + size).to_string());
+
+        let mut payload = Map::new();
+        payload.insert(field.clone(), Value::Array(page));
+        Some(Page {
+            payload: Value::Object(payload),
+            next_cursor,
+        })
+    }
+
+    fn empty_collection(&self, path: &str) -> Option<Page> {
+        let name = path.rsplit('/').next()?;
+        let field = self
+            .documents
+            .iter()
+            .find_map(|(other, document)| match document {
+                Document::Collection { field, .. } if other.rsplit('/').next() == Some(name) => {
+                    Some(field)
+                }
+                _ => None,
+            })?;
+
+        let mut payload = Map::new();
+        payload.insert(field.clone(), Value::Array(Vec::new()));
+        Some(Page {
+            payload: Value::Object(payload),
+            next_cursor: None,
+        })
+    }
+
+    pub fn get(&self, path: &str) -> Option<Value> {
+        if let Some(Document::Single(payload)) = self.documents.get(path) {
+            return Some(payload.clone());
+        }
+        let (collection, id) = path.rsplit_once('/')?;
+        self.items(collection)?
+            .iter()
+            .find(|item| has_id(item, id))
+            .cloned()
+    }
+
+    /// Add an item to the collection at the path, creating the collection if
+    /// there's no fixture for it. The item gets an ID if it doesn't have one.
+    pub fn create(&mut self, path: &str, mut item: Value) -> Option<Value> {
+        let object = item.as_object_mut()?;
+        object
+            .entry("id")
+            .or_insert_with(|| Value::String(format!("{:016x}", rand::random::<u64>())));
+        object.insert("createdAt".into(), now());
+
+        let document =
+            self.documents
+                .entry(path.to_string())
+                .or_insert_with(|| Document::Collection {
+                    field: collection_field(path),
+                    items: Vec::new(),
+                });
+        match document {
+            Document::Collection { items, .. } => {
+                items.push(item.clone());
+                Some(item)
+            }
+            Document::Single(_) => None,
+        }
+    }
+
+    /// Replace an item, or with `merge` only the fields given. The ID always
+    /// stays the same.
+    pub fn update(&mut self, path: &str, body: Value, merge: bool) -> Option<Value> {
+        let (collection, id) = path.rsplit_once('/')?;
+        let item = self
+            .items_mut(collection)?
+            .iter_mut()
+            .find(|item| has_id(item, id))?;
+        let Value::Object(fields) = body else {
+            return None;
+        };
+
+        let original_id = item.get("id").cloned();
+        let object = item.as_object_mut()?;
+        if !merge {
+            object.retain(|name, _| name == "createdAt");
+        }
+        object.extend(fields);
+        if let Some(original_id) = original_id {
+            object.insert("id".into(), original_id);
+        }
+        object.insert("updatedAt".into(), now());
+        Some(item.clone())
+    }
+
+    /// Remove an item, returning whether there was one to remove
+    pub fn delete(&mut self, path: &str) -> bool {
+        let Some((collection, id)) = path.rsplit_once('/') else {
+            return false;
+        };
+        let Some(items) = self.items_mut(collection) else {
+            return false;
+        };
+        let before = items.len();
+        items.retain(|item| !has_id(item, id));
+        items.len() != before
+    }
+
+    fn items(&self, path: &str) -> Option<&Vec<Value>> {
+        match self.documents.get(path)? {
+            Document::Collection { items, .. } => Some(items),
+            Document::Single(_) => None,
+        }
+    }
+
+    fn items_mut(&mut self, path: &str) -> Option<&mut Vec<Value>> {
+        match self.documents.get_mut(path)? {
+            Document::Collection { items, .. } => Some(items),
+            Document::Single(_) => None,
+        }
+    }
+}
+
+impl Document {
+    fn from_payload(payload: Value) -> Self {
+        if let Value::Object(map) = &payload {
+            if let [(field, Value::Array(items))] = map.iter().collect::<Vec<_>>().as_slice() {
+                return Self::Collection {
+                    field: field.to_string(),
+                    items: items.clone(),
+                };
+            }
+        }
+        Self::Single(payload)
+    }
+}
+
+/// IDs are compared as text, since organizations have numeric IDs
+fn has_id(item: &Value, id: &str) -> bool {
+    match item.get("id") {
+        Some(Value::String(item_id)) => item_id == id,
+        Some(Value::Number(item_id)) => item_id.to_string() == id,
+        _ => false,
+    }
+}
+
+/// The payload field for a collection with no fixture, following the API's
+/// naming, e.g. `translation-sets` holds `translationSets`
+fn collection_field(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let mut field = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '-' {
+            upper = true;
+        } else if upper {
+            field.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            field.push(c);
+        }
+    }
+    field
+}
+
+fn now() -> Value {
+    Value::String(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn json_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Error reading fixtures in {dir:?}"))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(json_files(&path)?);
+        } else if path.extension().is_some_and(|e| e == "json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,126 @@
+//! Point a real client at the mock server, so the client and the mock are
+//! checked against each other
+
+use redox_api::{
+    models::{
+        alert::{AlertResource, AlertStatus, AlertStatusBody},
+        environment::EnvironmentResource,
+        RequestType,
+    },
+    RedoxApiError, RedoxRequestClient,
+};
+use redox_mock_server::{MockOptions, MockServer};
+
+mod common;
+
+use common::{client, fixtures};
+
+async fn environment_names(client: &RedoxRequestClient) -> Result<Vec<String>, RedoxApiError> {
+    Ok(client
+        .make_request(RequestType::List, EnvironmentResource::new(1))
+        .await?
+        .into_list()
+        .map(|list| list.environments)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|environment| environment.name)
+        .collect())
+}
+
+#[tokio::test]
+async fn authenticates_and_lists_environments() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let names = environment_names(&client(&server)).await.unwrap();
+    assert_eq!(names, ["Development", "Staging", "Production"]);
+}
+
+#[tokio::test]
+async fn follows_pages() {
+    let options = MockOptions {
+        page_size: Some(1),
+        ..Default::default()
+    };
+    let server = MockServer::start(&fixtures(), options).await.unwrap();
+    let names = environment_names(&client(&server)).await.unwrap();
+    assert_eq!(names, ["Development", "Staging", "Production"]);
+}
+
+#[tokio::test]
+async fn keeps_the_payload_of_every_page() {
+    let options = MockOptions {
+        page_size: Some(1),
+        ..Default::default()
+    };
+    let server = MockServer::start(&fixtures(), options).await.unwrap();
+    let (_, payload) = client(&server)
+        .make_request_with_payload(RequestType::List, EnvironmentResource::new(1))
+        .await
+        .unwrap();
+
+    // region isn't in the model, but is in what the API sent
+    let regions: Vec<_> = payload["environments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|environment| environment["region"].as_str().unwrap())
+        .collect();
+    assert_eq!(regions, ["us-east-1", "us-east-1", "us-west-2"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refreshes_a_rejected_token_once_for_concurrent_requests() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    client.refresh_jwt().await.unwrap();
+    assert_eq!(server.tokens_issued(), 1);
+
+    // the client still thinks its token is good, so every request gets a 401
+    server.expire_tokens();
+    let requests: Vec<_> = (0..8)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { environment_names(&client).await })
+        })
+        .collect();
+    for request in requests {
+        assert_eq!(request.await.unwrap().unwrap().len(), 3);
+    }
+    assert_eq!(server.tokens_issued(), 2);
+}
+
+#[tokio::test]
+async fn moves_alerts_to_a_new_status() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    let resource = AlertResource::new(1, "env-dev");
+
+    for (body, status) in [
+        (AlertStatusBody::acknowledge(), AlertStatus::Acknowledged),
+        (AlertStatusBody::resolve(), AlertStatus::Resolved),
+    ] {
+        let alert = client
+            .make_request(
+                RequestType::Update("alert-1".into(), body),
+                resource.clone(),
+            )
+            .await
+            .unwrap()
+            .into_single()
+            .unwrap();
+        assert_eq!(alert.status, status);
+    }
+
+    let alert = client
+        .make_request(RequestType::Get("alert-1".into()), resource)
+        .await
+        .unwrap()
+        .into_single()
+        .unwrap();
+    assert_eq!(alert.status, AlertStatus::Resolved);
+}
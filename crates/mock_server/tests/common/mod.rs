@@ -0,0 +1,22 @@
+//! Helpers shared by the tests that point a real client at the mock server
+
+use std::path::{Path, PathBuf};
+
+use redox_api::{key::Key, RedoxRequestClient};
+use redox_mock_server::MockServer;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// A client authenticating as the credential in the fixtures
+pub fn client(server: &MockServer) -> RedoxRequestClient {
+    let pem = std::fs::read_to_string(manifest_dir().join("dev-key.pem")).unwrap();
+    let key = Key::from_pem(&pem, None, None).unwrap();
+    let url = server.url();
+    RedoxRequestClient::new(&url, Some(&url), key, "mock-dev-key", "mock-client").unwrap()
+}
+
+pub fn fixtures() -> PathBuf {
+    manifest_dir().join("fixtures")
+}
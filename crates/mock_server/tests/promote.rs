@@ -0,0 +1,71 @@
+//! Promote items between the mock server's environments
+
+use redox_api::{
+    promote::{Promotion, PromotionItems},
+    snapshot::{ConfigKind, EnvironmentSnapshot, RestoreAction},
+};
+use redox_mock_server::{MockOptions, MockServer};
+
+mod common;
+
+use common::{client, fixtures};
+
+#[tokio::test]
+async fn applies_a_plan_with_attachments_remapped() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    let promotion = Promotion::fetch(&client, 1, "env-dev", "env-prod")
+        .await
+        .unwrap();
+
+    // production has no endpoints to attach the filter to yet
+    let filter = PromotionItems {
+        filters: vec!["Drop test patients".into()],
+        ..Default::default()
+    };
+    assert_eq!(promotion.plan(&filter).missing_endpoints, ["Patient app"]);
+
+    let dev = promotion.source().clone();
+    let endpoints = EnvironmentSnapshot {
+        sources: dev.sources.clone(),
+        destinations: dev.destinations.clone(),
+        ..Default::default()
+    };
+    endpoints
+        .restore(&client, 1, "env-prod", false)
+        .await
+        .unwrap();
+
+    let promotion = Promotion::fetch(&client, 1, "env-dev", "env-prod")
+        .await
+        .unwrap();
+    let plan = promotion.plan(&filter);
+    assert!(plan.missing_endpoints.is_empty());
+    let promoted = plan.apply(&client, 1, "env-prod").await.unwrap();
+    assert_eq!(promoted.len(), 1);
+    assert_eq!(promoted[0].kind, ConfigKind::Filter);
+    assert_eq!(promoted[0].action, RestoreAction::Create);
+
+    let prod = EnvironmentSnapshot::fetch(&client, 1, "env-prod")
+        .await
+        .unwrap();
+    assert_eq!(prod.filters.len(), 1);
+    assert_eq!(
+        prod.filters[0].attached_to.destinations,
+        [prod.destinations[0].id.clone()]
+    );
+    assert_eq!(prod.filters[0].rules, dev.filters[0].rules);
+
+    // promoting again finds nothing to change
+    let promotion = Promotion::fetch(&client, 1, "env-dev", "env-prod")
+        .await
+        .unwrap();
+    let plan = promotion.plan(&filter);
+    assert!(plan.is_empty());
+    assert_eq!(
+        plan.unchanged,
+        [(ConfigKind::Filter, "Drop test patients".to_string())]
+    );
+}
@@ -0,0 +1,217 @@
+//! Restore snapshots into the mock server's environments, so what a restore
+//! writes can be checked by fetching the environment again
+
+use redox_api::{
+    snapshot::{ConfigKind, EnvironmentSnapshot, RestoreAction, RestoredItem},
+    RedoxRequestClient,
+};
+use redox_mock_server::{MockOptions, MockServer};
+
+mod common;
+
+use common::{client, fixtures};
+
+async fn snapshot(client: &RedoxRequestClient, environment_id: &str) -> EnvironmentSnapshot {
+    EnvironmentSnapshot::fetch(client, 1, environment_id)
+        .await
+        .unwrap()
+}
+
+fn summary(restored: &[RestoredItem]) -> Vec<(ConfigKind, &str, RestoreAction)> {
+    restored
+        .iter()
+        .map(|item| (item.kind, item.name.as_str(), item.action))
+        .collect()
+}
+
+/// The IDs restored items ended up with in the target, by name
+fn restored_id<'a>(restored: &'a [RestoredItem], name: &str) -> Option<&'a str> {
+    restored
+        .iter()
+        .find(|item| item.name == name)
+        .and_then(|item| item.id.as_deref())
+}
+
+#[tokio::test]
+async fn updates_items_with_the_same_name_and_creates_the_rest() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    let mut dev = snapshot(&client, "env-dev").await;
+    let mut inactive = dev.filters[0].clone();
+    inactive.id = "flt-dev-inactive".into();
+    inactive.name = "Drop inactive patients".into();
+    dev.filters.push(inactive);
+
+    let restored = dev.restore(&client, 1, "env-staging", false).await.unwrap();
+
+    assert_eq!(
+        summary(&restored),
+        [
+            (ConfigKind::Source, "EHR", RestoreAction::Update),
+            (
+                ConfigKind::Destination,
+                "Patient app",
+                RestoreAction::Update
+            ),
+            (
+                ConfigKind::Filter,
+                "Drop test patients",
+                RestoreAction::Update
+            ),
+            (
+                ConfigKind::Filter,
+                "Drop inactive patients",
+                RestoreAction::Create
+            ),
+            (
+                ConfigKind::ConfigModifier,
+                "Default facility",
+                RestoreAction::Update
+            ),
+            (
+                ConfigKind::TranslationSet,
+                "Administrative sex",
+                RestoreAction::Update
+            ),
+        ]
+    );
+    // updates keep the target's IDs
+    assert_eq!(restored_id(&restored, "EHR"), Some("src-staging-ehr"));
+    assert_eq!(
+        restored_id(&restored, "Drop test patients"),
+        Some("flt-staging-test-patients")
+    );
+
+    let staging = snapshot(&client, "env-staging").await;
+    assert_eq!(staging.destinations.len(), 1);
+    assert_eq!(staging.destinations[0].id, "dst-staging-app");
+    assert_eq!(
+        staging.destinations[0].endpoint,
+        dev.destinations[0].endpoint
+    );
+    let created = staging
+        .filters
+        .iter()
+        .find(|f| f.name == "Drop inactive patients")
+        .unwrap();
+    assert_eq!(
+        Some(created.id.as_str()),
+        restored_id(&restored, "Drop inactive patients")
+    );
+    assert_ne!(created.id, "flt-dev-inactive");
+}
+
+#[tokio::test]
+async fn remaps_snapshot_ids_into_attached_to() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    let dev = snapshot(&client, "env-dev").await;
+
+    // production has nothing yet, so everything is created with new IDs
+    let restored = dev.restore(&client, 1, "env-prod", false).await.unwrap();
+    assert!(restored
+        .iter()
+        .all(|item| item.action == RestoreAction::Create));
+
+    let prod = snapshot(&client, "env-prod").await;
+    let source_id = prod.sources[0].id.clone();
+    let destination_id = prod.destinations[0].id.clone();
+    assert_ne!(source_id, "src-dev-ehr");
+    assert_ne!(destination_id, "dst-dev-app");
+    assert_eq!(prod.filters[0].attached_to.destinations, [destination_id]);
+    assert_eq!(prod.config_modifiers[0].attached_to.sources, [source_id]);
+
+    // restoring over the same names maps to the existing IDs instead
+    dev.restore(&client, 1, "env-staging", false).await.unwrap();
+    let staging = snapshot(&client, "env-staging").await;
+    assert_eq!(
+        staging.filters[0].attached_to.destinations,
+        ["dst-staging-app"]
+    );
+    assert_eq!(
+        staging.config_modifiers[0].attached_to.sources,
+        ["src-staging-ehr"]
+    );
+}
+
+#[tokio::test]
+async fn dry_run_changes_nothing() {
+    let server = MockServer::start(&fixtures(), MockOptions::default())
+        .await
+        .unwrap();
+    let client = client(&server);
+    let dev = snapshot(&client, "env-dev").await;
+    let staging_before = serde_json::to_value(snapshot(&client, "env-staging").await).unwrap();
+
+    let restored = dev.restore(&client, 1, "env-staging", true).await.unwrap();
+    assert!(restored
+        .iter()
+        .all(|item| item.action == RestoreAction::Update));
+    assert_eq!(restored_id(&restored, "EHR"), Some("src-staging-ehr"));
+    // updates would change updatedAt, and creates would add items
+    let staging_after = serde_json::to_value(snapshot(&client, "env-staging").await).unwrap();
+    assert_eq!(staging_before, staging_after);
+
+    // the IDs of items that would be created aren't known
+    let restored = dev.restore(&client, 1, "env-prod", true).await.unwrap();
+    assert_eq!(restored.len(), dev.len());
+    assert!(restored
+        .iter()
+        .all(|item| item.action == RestoreAction::Create && item.id.is_none()));
+    assert!(snapshot(&client, "env-prod").await.is_empty());
+}
+
+#[tokio::test]
+async fn stops_at_a_failed_write_with_what_was_restored() {
+    let options = MockOptions {
+        reject_writes: vec!["platform/v1/organizations/1/environments/env-prod/filters".into()],
+        ..Default::default()
+    };
+    let server = MockServer::start(&fixtures(), options).await.unwrap();
+    let client = client(&server);
+    let dev = snapshot(&client, "env-dev").await;
+
+    let error = dev
+        .restore(&client, 1, "env-prod", false)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error.failed,
+        Some((ConfigKind::Filter, "Drop test patients".to_string()))
+    );
+    assert_eq!(error.source.status().map(|s| s.as_u16()), Some(422));
+    assert_eq!(
+        summary(&error.restored),
+        [
+            (ConfigKind::Source, "EHR", RestoreAction::Create),
+            (
+                ConfigKind::Destination,
+                "Patient app",
+                RestoreAction::Create
+            ),
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "Failed to restore Filter Drop test patients after restoring 2 items"
+    );
+
+    // what was reported as restored is really there, and nothing after it
+    let prod = snapshot(&client, "env-prod").await;
+    assert_eq!(
+        Some(prod.sources[0].id.as_str()),
+        restored_id(&error.restored, "EHR")
+    );
+    assert_eq!(
+        Some(prod.destinations[0].id.as_str()),
+        restored_id(&error.restored, "Patient app")
+    );
+    assert!(prod.filters.is_empty());
+    assert!(prod.config_modifiers.is_empty());
+    assert!(prod.translation_sets.is_empty());
+}
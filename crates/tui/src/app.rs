@@ -1,8 +1,13 @@
 use chrono::{DateTime, Local, Utc};
 use iocraft::prelude::*;
+use redox_api::RedoxApiError;
+use redox_core::Selection;
 use tracing::{info, Level};
 
-use crate::{pages::primary::PrimaryPage, shared_components::BoxWithTitle};
+use crate::{
+    pages::{compare::CompareTarget, diff::DiffPage, primary::PrimaryPage},
+    shared_components::BoxWithTitle,
+};
 
 mod context;
 pub use context::AppContext;
@@ -24,23 +29,31 @@ impl ReportedEvent {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum CurrentPage {
     Primary,
+    /// Comparing the current environment with another
+    Diff,
+}
+
+#[derive(Default, Props)]
+pub struct AppProps {
+    pub selection: Selection,
 }
 
 #[component]
-pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
+pub fn App(mut hooks: Hooks, props: &AppProps) -> impl Into<AnyElement<'static>> {
     let (width, height) = hooks.use_terminal_size();
     let mut system = hooks.use_context_mut::<SystemContext>();
 
     let mut should_exit = hooks.use_state(|| false);
-    let mut events = hooks.use_state::<Vec<ReportedEvent>, _>(|| vec![]);
+    let mut events = hooks.use_state::<Vec<ReportedEvent>, _>(Vec::new);
     let mut event_reporter_focus = hooks.use_state(|| false);
 
-    let cur_page = hooks.use_state(|| CurrentPage::Primary);
+    let mut cur_page = hooks.use_state(|| CurrentPage::Primary);
 
-    let mut app_context = hooks.use_state(|| AppContext::default());
+    let selection = props.selection.clone();
+    let mut app_context = hooks.use_state(move || AppContext::new(selection));
 
     let mut report_event = move |event: ReportedEvent| {
         info!("Event: {}", event.message);
@@ -51,41 +64,73 @@ pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
 
     let mut load_config = hooks.use_async_handler(move |_| async move {
         let mut current_context = app_context.read().clone();
-        current_context.load_configuration().await;
+        let config_result = current_context.load_configuration().await;
+        let org_result = current_context.load_organizations().await;
         app_context.set(current_context);
-        report_event(ReportedEvent::new(
-            Level::INFO,
-            "Loaded configuration".into(),
-        ));
+        report_event(match config_result {
+            Ok(()) => ReportedEvent::new(Level::INFO, "Loaded configuration".into()),
+            Err(e) => {
+                ReportedEvent::new(Level::ERROR, format!("Failed to load configuration. {e:#}"))
+            }
+        });
+
+        let cur_ctx = app_context.read().clone();
+        match org_result {
+            Ok(()) if cur_ctx.api_client.is_some() => {
+                report_event(ReportedEvent::new(
+                    Level::INFO,
+                    format!("{} organizations available", cur_ctx.organizations.len()),
+                ));
+                if let Some(org_id) = cur_ctx.default_organization() {
+                    if !cur_ctx.is_known_organization(org_id) {
+                        report_event(ReportedEvent::new(
+                            Level::WARN,
+                            format!("Default org {org_id} is not accessible with this credential"),
+                        ));
+                    }
+                }
+            }
+            Ok(()) => {}
+            Err(e) => report_event(api_error_event(&e, "organizations")),
+        }
     });
 
     let mut update_environments = hooks.use_async_handler(move |_: ()| async move {
         let mut current_context = app_context.read().clone();
-        current_context.load_environments().await;
+        let result = current_context.load_environments().await;
         app_context.set(current_context);
-        report_event(ReportedEvent::new(
-            Level::INFO,
-            format!(
-                "Environments for org {} loaded",
-                app_context
-                    .read()
-                    .current_organization
-                    .map_or("none".to_string(), |o| o.to_string())
-            ),
-        ));
+        let org = app_context.read().current_organization_name();
+        let event = match result {
+            Ok(()) => ReportedEvent::new(Level::INFO, format!("Environments for org {org} loaded")),
+            Err(e) => api_error_event(&e, &format!("environments for org {org}")),
+        };
+        report_event(event);
     });
 
     let mut handle_org_change = move |org_id: Option<i32>| {
         let mut new_context = app_context.read().clone();
         new_context.current_organization = org_id;
+        // environments belong to an org, so don't carry them over
+        new_context.env_ctx = Default::default();
         app_context.set(new_context);
         report_event(ReportedEvent::new(
             Level::INFO,
-            "Organization changed".into(),
+            format!(
+                "Organization changed to {}",
+                app_context.read().current_organization_name()
+            ),
         ));
         update_environments(());
     };
 
+    // Set by the organization picker on the primary page
+    let mut requested_org = hooks.use_state(|| None::<i32>);
+    // Set by the compare picker on the primary page, and cleared when the
+    // diff page is closed
+    let compare_target = hooks.use_state(|| None::<CompareTarget>);
+    // Set by the diff page while it's reading typed text
+    let text_input = hooks.use_state(|| false);
+
     hooks.use_terminal_events({
         move |event| match event {
             TerminalEvent::Key(KeyEvent {
@@ -93,7 +138,7 @@ pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                 kind,
                 modifiers,
                 ..
-            }) if kind != KeyEventKind::Release => match (code, modifiers) {
+            }) if kind != KeyEventKind::Release && !text_input.get() => match (code, modifiers) {
                 (KeyCode::Char('q'), _) => should_exit.set(true),
                 (KeyCode::Char('R'), KeyModifiers::SHIFT) => {
                     event_reporter_focus.set(!event_reporter_focus.get())
@@ -108,19 +153,28 @@ pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
         system.exit();
     }
 
-    if matches!(app_context.read().configuration, None) {
+    let page = match compare_target.read().is_some() {
+        true => CurrentPage::Diff,
+        false => CurrentPage::Primary,
+    };
+    if cur_page.get() != page {
+        cur_page.set(page);
+    }
+
+    if app_context.read().configuration.is_none() {
         load_config(());
     }
 
     {
         let cur_ctx = app_context.read().clone();
-        if cur_ctx.current_organization.is_none() {
-            // if our context has a deployment with a default_organization, set it
-            if let Some(org_id) = cur_ctx
-                .current_deployment
-                .as_ref()
-                .and_then(|d| d.default_org)
-            {
+        if let Some(org_id) = requested_org.get() {
+            requested_org.set(None);
+            if cur_ctx.current_organization != Some(org_id) {
+                handle_org_change(Some(org_id));
+            }
+        } else if cur_ctx.current_organization.is_none() {
+            // if our context has a default organization, set it
+            if let Some(org_id) = cur_ctx.default_organization() {
                 handle_org_change(Some(org_id));
             }
         }
@@ -140,7 +194,8 @@ pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                     flex_grow: 1.0
                 ){
                     #(match cur_page.get() {
-                        CurrentPage::Primary => element! { PrimaryPage }.into_any(),
+                        CurrentPage::Primary => element! { PrimaryPage(requested_org: Some(requested_org), compare_target: Some(compare_target)) }.into_any(),
+                        CurrentPage::Diff => element! { DiffPage(compare_target: Some(compare_target), text_input: Some(text_input)) }.into_any(),
                     })
                 }
             }
@@ -149,6 +204,26 @@ pub fn App(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
     }
 }
 
+/// Describe a failed request for the event reporter, calling out the failures
+/// the user can do something about
+fn api_error_event(error: &RedoxApiError, loading: &str) -> ReportedEvent {
+    if error.is_unauthorized() {
+        ReportedEvent::new(
+            Level::ERROR,
+            "Credentials were rejected, check the deployment's auth settings".into(),
+        )
+    } else if error.is_forbidden() {
+        ReportedEvent::new(Level::ERROR, format!("Not permitted to view {loading}"))
+    } else if error.is_server_error() {
+        ReportedEvent::new(
+            Level::WARN,
+            format!("Redox is having trouble, try again later. {error}"),
+        )
+    } else {
+        ReportedEvent::new(Level::ERROR, format!("Failed to load {loading}. {error}"))
+    }
+}
+
 #[derive(Default, Props)]
 pub struct EventReporterProps {
     events: Vec<ReportedEvent>,
@@ -1,14 +1,12 @@
-use std::sync::{Arc, Mutex};
-
 use redox_api::{
     models::{
         environment::{Environment, EnvironmentFlag, EnvironmentResource},
+        organization::{Organization, OrganizationResource},
         RequestType,
     },
-    RedoxRequestClient, Response,
+    RedoxApiError, RedoxRequestClient, Response,
 };
-use redox_core::{Configuration, ConfigurationFile, Deployment};
-use tokio::spawn;
+use redox_core::{util::ResultTraced, Configuration, ConfigurationFile, Deployment, Selection};
 
 #[derive(Default, Clone)]
 pub struct EnvironmentContext {
@@ -18,83 +16,135 @@ pub struct EnvironmentContext {
 
 #[derive(Default, Clone)]
 pub struct AppContext {
+    /// What was picked at startup, which takes precedence over the defaults
+    /// in the configuration
+    pub selection: Selection,
     pub configuration: Option<Configuration>,
     pub current_deployment: Option<Deployment>,
     pub current_organization: Option<i32>,
+    /// Every organization the current deployment's credential can access
+    pub organizations: Vec<Organization>,
     pub env_ctx: EnvironmentContext,
-    pub api_client: Option<Arc<Mutex<RedoxRequestClient>>>,
+    pub api_client: Option<RedoxRequestClient>,
 }
 
 impl AppContext {
-    pub async fn load_configuration(&mut self) {
-        let configuration_path = ConfigurationFile::try_path(None, None).unwrap();
-        let configuration_file = ConfigurationFile::load(configuration_path.clone())
-            .await
-            .unwrap_or_else(|_| ConfigurationFile::with_path(configuration_path));
+    pub fn new(selection: Selection) -> Self {
+        Self {
+            selection,
+            ..Default::default()
+        }
+    }
 
-        self.configuration = Some(configuration_file.configuration);
+    /// Load the configuration file and switch to the selected deployment. If
+    /// the file can't be found or loaded an empty configuration is used, so
+    /// the TUI still starts and can show the error.
+    pub async fn load_configuration(&mut self) -> anyhow::Result<()> {
+        let configuration = match self.selection.config_path() {
+            Ok(path) => ConfigurationFile::load(path)
+                .await
+                .map(|file| file.configuration),
+            Err(e) => Err(e),
+        };
+        self.configuration = Some(configuration.as_ref().cloned().unwrap_or_default());
+        let configuration = configuration?;
 
-        // If the configuration has a deployment with default, set the cur_deployment
-        if let Some(deployment) = self
-            .configuration
-            .as_ref()
-            .map_or(vec![], |c| c.deployments.clone())
-            .iter()
-            .find(|d| d.default == Some(true))
-        {
+        if let Some(deployment) = self.selection.deployment(&configuration)? {
             self.current_deployment = Some(deployment.clone());
             self.load_auth_client();
         }
+        Ok(())
     }
 
     pub fn load_auth_client(&mut self) {
         if let Some(deployment) = self.current_deployment.as_ref() {
-            let new_auth_client = RedoxRequestClient::new(
-                &deployment.api_host,
-                deployment.auth_host.as_deref(),
-                &deployment.auth.private_key_file,
-                &deployment.auth.kid,
-                &deployment.auth.client_id,
-            )
-            .map_or(None, |f| Some(Arc::new(Mutex::new(f))));
+            let new_auth_client = RedoxRequestClient::for_deployment(deployment)
+                .map_err(anyhow::Error::from)
+                .traced()
+                .ok();
 
             if let Some(client) = new_auth_client {
-                let client_clone = Arc::clone(&client);
-
-                spawn(async move {
-                    let mut client = client_clone.lock().unwrap().clone();
-                    client.refresh_jwt().await
-                });
-
+                // Fetches the first token right away and keeps it fresh from
+                // then on, so requests rarely wait on the auth host
+                client.spawn_token_refresh();
                 self.api_client = Some(client);
             }
         }
     }
 
-    pub async fn load_environments(&mut self) {
+    pub async fn load_organizations(&mut self) -> Result<(), RedoxApiError> {
+        if let Some(client) = self.api_client.as_ref() {
+            self.organizations = client
+                .make_request(RequestType::List, OrganizationResource::new())
+                .await
+                .map(|response| match response {
+                    Response::List(payload) => payload.organizations,
+                    _ => vec![],
+                })?;
+        }
+        Ok(())
+    }
+
+    /// The organization to start with: the one selected at startup, then the
+    /// deployment's default, and otherwise the only organization available
+    pub fn default_organization(&self) -> Option<i32> {
+        self.current_deployment
+            .as_ref()
+            .and_then(|d| self.selection.org(d))
+            .or(match self.organizations.as_slice() {
+                [only] => Some(only.id),
+                _ => None,
+            })
+    }
+
+    /// Whether the organization is one the credential can access. Always true
+    /// if the organizations haven't been loaded, since we can't say otherwise.
+    pub fn is_known_organization(&self, org_id: i32) -> bool {
+        self.organizations.is_empty() || self.organizations.iter().any(|o| o.id == org_id)
+    }
+
+    /// A display name for the current organization
+    pub fn current_organization_name(&self) -> String {
+        match self.current_organization {
+            Some(org_id) => self
+                .organizations
+                .iter()
+                .find(|o| o.id == org_id)
+                .map_or(org_id.to_string(), |o| format!("{} [{}]", o.name, o.id)),
+            None => "none".to_string(),
+        }
+    }
+
+    pub async fn load_environments(&mut self) -> Result<(), RedoxApiError> {
         if let (Some(client), Some(org_id)) =
             (self.api_client.as_ref(), self.current_organization.as_ref())
         {
-            let mut req_client = client.lock().unwrap().clone();
-            let environments = req_client
+            // list requests follow every page, so large orgs aren't truncated
+            let environments = client
                 .make_request(RequestType::List, EnvironmentResource::new(*org_id))
                 .await
                 .map(|response| match response {
                     Response::List(payload) => payload.environments,
                     _ => vec![],
-                })
-                .unwrap_or(vec![]);
-            self.env_ctx.environments = environments;
+                });
+            self.env_ctx.environments = environments?;
 
-            // if the environment list has a Development flag one, set that to current
-            if let Some(env) = self
-                .env_ctx
-                .environments
-                .iter()
-                .find(|e| e.environment_flag == EnvironmentFlag::Development)
-            {
+            // start with the selected environment if there is one, otherwise
+            // the development one
+            let environments = &self.env_ctx.environments;
+            let selected = self
+                .selection
+                .env
+                .as_deref()
+                .and_then(|env| environments.iter().find(|e| e.matches(env)));
+            if let Some(env) = selected.or_else(|| {
+                environments
+                    .iter()
+                    .find(|e| e.environment_flag == EnvironmentFlag::Development)
+            }) {
                 self.env_ctx.current_environment = Some(env.clone());
             }
         }
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use iocraft::{element, ElementExt};
+use redox_core::Selection;
 
 mod app;
 mod pages;
@@ -9,7 +10,12 @@ use app::App;
 pub struct Tui;
 
 impl Tui {
-    pub async fn start() {
-        element!(App).fullscreen().await.unwrap()
+    /// Run the TUI until the user quits, starting with the given deployment,
+    /// organization and environment where they're set
+    pub async fn start(selection: Selection) {
+        element!(App(selection: selection))
+            .fullscreen()
+            .await
+            .unwrap()
     }
 }
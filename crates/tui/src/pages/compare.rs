@@ -0,0 +1,365 @@
+use iocraft::{
+    hooks::{State, UseAsyncHandler, UseContext, UseState},
+    prelude::{component, element, AnyElement, Box as IoBox, Props, Text},
+    Color, Hooks,
+};
+use redox_api::{
+    models::{
+        environment::{Environment, EnvironmentResource},
+        organization::{Organization, OrganizationResource},
+        RequestType,
+    },
+    RedoxApiError, RedoxRequestClient,
+};
+use redox_core::Deployment;
+
+use crate::{
+    app::AppContext,
+    shared_components::{ItemRenderer, ListBox},
+};
+
+/// An environment to compare the current one with, along with the client and
+/// organization to reach it through. It can be in any organization of any
+/// configured deployment.
+#[derive(Clone)]
+pub struct CompareTarget {
+    pub client: RedoxRequestClient,
+    pub deployment: String,
+    pub org_id: i32,
+    pub environment: Environment,
+}
+
+impl CompareTarget {
+    /// Whether the environment is in the current deployment and organization
+    pub fn is_local(&self, ctx: &AppContext) -> bool {
+        ctx.current_deployment
+            .as_ref()
+            .is_some_and(|d| d.name == self.deployment)
+            && ctx.current_organization == Some(self.org_id)
+    }
+
+    /// The environment's name, and where it is if that's somewhere else
+    pub fn describe(&self, ctx: &AppContext) -> String {
+        if self.is_local(ctx) {
+            self.environment.name.clone()
+        } else {
+            format!(
+                "{} [{} org {}]",
+                self.environment.name, self.deployment, self.org_id
+            )
+        }
+    }
+}
+
+/// Where the picker is up to. Each list is only shown once it's loaded, and
+/// steps with a single choice are skipped.
+#[derive(Clone)]
+enum Step {
+    Deployments(Vec<Deployment>),
+    Organizations {
+        deployment: String,
+        client: RedoxRequestClient,
+        organizations: Vec<Organization>,
+    },
+    Environments {
+        deployment: String,
+        client: RedoxRequestClient,
+        org_id: i32,
+        environments: Vec<Environment>,
+    },
+    Loading(String),
+    Failed(String),
+}
+
+/// The current environment, so it isn't offered as something to compare with
+#[derive(Clone)]
+struct Current {
+    deployment: Option<String>,
+    org_id: Option<i32>,
+    environment_id: Option<String>,
+}
+
+#[derive(Default, Props)]
+pub struct ComparePickerProps {
+    /// Set to the environment the user picks
+    pub compare_target: Option<State<Option<CompareTarget>>>,
+}
+
+/// Pick an environment to compare the current one with, going through the
+/// configured deployments and their organizations to find it
+#[component]
+pub fn ComparePicker(
+    mut hooks: Hooks,
+    props: &ComparePickerProps,
+) -> impl Into<AnyElement<'static>> {
+    let cur_ctx = hooks.use_context::<AppContext>();
+    let current = Current {
+        deployment: cur_ctx.current_deployment.as_ref().map(|d| d.name.clone()),
+        org_id: cur_ctx.current_organization,
+        environment_id: cur_ctx
+            .env_ctx
+            .current_environment
+            .as_ref()
+            .map(|e| e.id.clone()),
+    };
+    let mut step = hooks.use_state({
+        let ctx = cur_ctx.clone();
+        let current = current.clone();
+        move || first_step(&ctx, &current)
+    });
+
+    let mut choose_deployment = hooks.use_async_handler({
+        let ctx = cur_ctx.clone();
+        let current = current.clone();
+        move |deployment: Deployment| {
+            let (ctx, current) = (ctx.clone(), current.clone());
+            async move {
+                step.set(Step::Loading(format!(
+                    "Loading organizations in {}...",
+                    deployment.name
+                )));
+                step.set(organizations_step(&ctx, &current, deployment).await);
+            }
+        }
+    });
+
+    let mut choose_organization = hooks.use_async_handler({
+        let current = current.clone();
+        move |(deployment, client, org_id): (String, RedoxRequestClient, i32)| {
+            let current = current.clone();
+            async move {
+                step.set(Step::Loading(format!(
+                    "Loading environments in organization {org_id}..."
+                )));
+                step.set(environments_step(&current, deployment, client, org_id).await);
+            }
+        }
+    });
+
+    let deployment_renderer: ItemRenderer<Deployment> = Box::new({
+        let current = current.deployment.clone();
+        move |deployment, is_selected| {
+            let label = match current.as_ref() == Some(&deployment.name) {
+                true => format!("{} (current)", deployment.name),
+                false => deployment.name.clone(),
+            };
+            list_item(label, is_selected)
+        }
+    });
+    let org_renderer: ItemRenderer<Organization> =
+        Box::new(|org, is_selected| list_item(format!("{} [{}]", org.name, org.id), is_selected));
+    let env_renderer: ItemRenderer<Environment> =
+        Box::new(|env, is_selected| list_item(format!("{} [{}]", env.name, env.id), is_selected));
+
+    let compare_target = props.compare_target;
+    let cur_step = step.read().clone();
+    match cur_step {
+        Step::Deployments(deployments) => {
+            let selected_index = deployments
+                .iter()
+                .position(|d| current.deployment.as_ref() == Some(&d.name))
+                .unwrap_or_default();
+            let choices = deployments.clone();
+            element! {
+                ListBox<Deployment>(
+                    is_selected: true,
+                    title: "Compare with: deployment (enter to pick)",
+                    items: deployments,
+                    item_renderer: deployment_renderer,
+                    selected_index,
+                    on_select: move |index: usize| {
+                        if let Some(deployment) = choices.get(index) {
+                            choose_deployment(deployment.clone());
+                        }
+                    },
+                )
+            }
+            .into_any()
+        }
+        Step::Organizations {
+            deployment,
+            client,
+            organizations,
+        } => {
+            let selected_index = organizations
+                .iter()
+                .position(|o| current.org_id == Some(o.id))
+                .unwrap_or_default();
+            let org_ids: Vec<i32> = organizations.iter().map(|o| o.id).collect();
+            element! {
+                ListBox<Organization>(
+                    is_selected: true,
+                    title: format!("Compare with: organization in {deployment} (enter to pick)"),
+                    items: organizations,
+                    item_renderer: org_renderer,
+                    selected_index,
+                    on_select: move |index: usize| {
+                        if let Some(org_id) = org_ids.get(index) {
+                            choose_organization((deployment.clone(), client.clone(), *org_id));
+                        }
+                    },
+                )
+            }
+            .into_any()
+        }
+        Step::Environments {
+            deployment,
+            client,
+            org_id,
+            environments,
+        } => {
+            let choices = environments.clone();
+            element! {
+                ListBox<Environment>(
+                    is_selected: true,
+                    title: "Compare with (enter to diff)",
+                    items: environments,
+                    item_renderer: env_renderer,
+                    on_select: move |index: usize| {
+                        if let (Some(mut compare_target), Some(environment)) =
+                            (compare_target, choices.get(index))
+                        {
+                            compare_target.set(Some(CompareTarget {
+                                client: client.clone(),
+                                deployment: deployment.clone(),
+                                org_id,
+                                environment: environment.clone(),
+                            }));
+                        }
+                    },
+                )
+            }
+            .into_any()
+        }
+        Step::Loading(message) => element! { Text(content: message) }.into_any(),
+        Step::Failed(message) => element! { Text(content: message, color: Color::Red) }.into_any(),
+    }
+}
+
+/// Start with the deployments if there's more than one to pick from,
+/// otherwise with what's already loaded for the current one
+fn first_step(ctx: &AppContext, current: &Current) -> Step {
+    let deployments = ctx
+        .configuration
+        .as_ref()
+        .map(|c| c.deployments.clone())
+        .unwrap_or_default();
+    if deployments.len() > 1 {
+        return Step::Deployments(deployments);
+    }
+    let (Some(deployment), Some(client)) = (&current.deployment, &ctx.api_client) else {
+        return Step::Failed("Pick a deployment first".into());
+    };
+
+    match (ctx.organizations.as_slice(), current.org_id) {
+        ([_], Some(org_id)) | ([], Some(org_id)) => Step::Environments {
+            deployment: deployment.clone(),
+            client: client.clone(),
+            org_id,
+            environments: others(
+                current,
+                deployment,
+                org_id,
+                ctx.env_ctx.environments.clone(),
+            ),
+        },
+        (organizations, _) => Step::Organizations {
+            deployment: deployment.clone(),
+            client: client.clone(),
+            organizations: organizations.to_vec(),
+        },
+    }
+}
+
+/// The organizations in a deployment, going straight to the environments if
+/// there's only one
+async fn organizations_step(ctx: &AppContext, current: &Current, deployment: Deployment) -> Step {
+    let result = async {
+        // Reuse the current deployment's client and what it's loaded
+        if current.deployment.as_ref() == Some(&deployment.name) {
+            if let Some(client) = &ctx.api_client {
+                return Ok((client.clone(), ctx.organizations.clone()));
+            }
+        }
+        let client = RedoxRequestClient::for_deployment(&deployment)?;
+        let organizations = client
+            .make_request(RequestType::List, OrganizationResource::new())
+            .await?
+            .into_list()
+            .map(|list| list.organizations)
+            .unwrap_or_default();
+        Ok::<_, RedoxApiError>((client, organizations))
+    }
+    .await;
+
+    match result {
+        Ok((client, organizations)) => match organizations.as_slice() {
+            [org] => environments_step(current, deployment.name, client, org.id).await,
+            _ => Step::Organizations {
+                deployment: deployment.name,
+                client,
+                organizations,
+            },
+        },
+        Err(e) => Step::Failed(format!(
+            "Failed to load organizations in {}. {e}",
+            deployment.name
+        )),
+    }
+}
+
+async fn environments_step(
+    current: &Current,
+    deployment: String,
+    client: RedoxRequestClient,
+    org_id: i32,
+) -> Step {
+    let environments = client
+        .make_request(RequestType::List, EnvironmentResource::new(org_id))
+        .await
+        .map(|response| {
+            response
+                .into_list()
+                .map(|list| list.environments)
+                .unwrap_or_default()
+        });
+    match environments {
+        Ok(environments) => Step::Environments {
+            environments: others(current, &deployment, org_id, environments),
+            deployment,
+            client,
+            org_id,
+        },
+        Err(e) => Step::Failed(format!(
+            "Failed to load environments in organization {org_id}. {e}"
+        )),
+    }
+}
+
+/// Every environment but the current one
+fn others(
+    current: &Current,
+    deployment: &str,
+    org_id: i32,
+    environments: Vec<Environment>,
+) -> Vec<Environment> {
+    let is_current_org =
+        current.deployment.as_deref() == Some(deployment) && current.org_id == Some(org_id);
+    environments
+        .into_iter()
+        .filter(|e| !is_current_org || current.environment_id.as_ref() != Some(&e.id))
+        .collect()
+}
+
+fn list_item(label: String, is_selected: bool) -> AnyElement<'static> {
+    let (color, background) = match is_selected {
+        true => (Color::Yellow, Color::DarkBlue),
+        false => (Color::Reset, Color::Reset),
+    };
+    element! {
+        IoBox(width: 100pct, background_color: Some(background)) {
+            Text(content: label, color: Some(color))
+        }
+    }
+    .into_any()
+}
@@ -0,0 +1,441 @@
+use std::collections::HashSet;
+
+use iocraft::{
+    hooks::{State, UseAsyncHandler, UseContext, UseFuture, UseState, UseTerminalEvents},
+    prelude::{component, element, AnyElement, BorderStyle, Box as IoBox, Props, Text},
+    Color, FlexDirection, Hooks, KeyCode, KeyEvent, KeyEventKind, TerminalEvent,
+};
+use redox_api::{
+    diff::{diff, flatten, Change, EnvironmentItems, ItemDiff, DEFAULT_KINDS},
+    models::{
+        environment::{Environment, EnvironmentFlag},
+        EnvironmentResources,
+    },
+    promote::{Promotion, PromotionItems},
+    RedoxApiError, RedoxRequestClient,
+};
+
+use crate::{
+    app::AppContext,
+    pages::compare::CompareTarget,
+    shared_components::{BoxWithTitle, ItemRenderer, ListBox},
+};
+
+#[derive(Clone)]
+enum DiffLoad {
+    Loading,
+    Loaded(Vec<ItemDiff>),
+    Failed(String),
+}
+
+#[derive(Default, Props)]
+pub struct DiffPageProps {
+    /// The environment being compared with the current one. Set to `None`
+    /// when the page is closed.
+    pub compare_target: Option<State<Option<CompareTarget>>>,
+    /// Set while the user is typing a production environment's name, so the
+    /// app's single key shortcuts leave the keys alone
+    pub text_input: Option<State<bool>>,
+}
+
+/// The differences between the current environment and another one, with the
+/// selected item's two versions side by side. Items can only be promoted to
+/// environments in the same organization.
+#[component]
+pub fn DiffPage(mut hooks: Hooks, props: &DiffPageProps) -> impl Into<AnyElement<'static>> {
+    let cur_ctx = hooks.use_context::<AppContext>();
+    let client = cur_ctx.api_client.clone();
+    let org_id = cur_ctx.current_organization;
+    let left = cur_ctx.env_ctx.current_environment.clone();
+    let target = props
+        .compare_target
+        .and_then(|target| target.read().clone());
+    let is_local = target.as_ref().is_some_and(|t| t.is_local(&cur_ctx));
+    let right_name = target
+        .as_ref()
+        .map_or("none".into(), |t| t.describe(&cur_ctx));
+    let right = target.as_ref().map(|t| t.environment.clone());
+
+    let mut load = hooks.use_state(|| DiffLoad::Loading);
+    let mut shown = hooks.use_state(|| None::<usize>);
+    // The item waiting for the user to confirm promoting it, and what they've
+    // typed so far when it's going to production
+    let mut confirming = hooks.use_state(|| None::<ItemDiff>);
+    let mut typed = hooks.use_state(String::new);
+    let mut status = hooks.use_state(|| None::<(String, Color)>);
+
+    hooks.use_future({
+        let (client, left, target) = (client.clone(), left.clone(), target.clone());
+        async move {
+            load.set(compare(client, org_id, left, target).await);
+        }
+    });
+
+    let mut promote = hooks.use_async_handler({
+        let (left, right, target) = (left.clone(), right.clone(), target.clone());
+        move |item: ItemDiff| {
+            let (client, left, right, target) =
+                (client.clone(), left.clone(), right.clone(), target.clone());
+            async move {
+                let (Some(api_client), Some(org), Some(from), Some(to)) =
+                    (&client, org_id, &left, &right)
+                else {
+                    return;
+                };
+                status.set(Some((
+                    format!("Promoting {} {} to {}...", item.kind, item.name, to.name),
+                    Color::Reset,
+                )));
+                let promoted = promote_item(api_client, org, from, to, &item).await;
+                status.set(Some(match promoted {
+                    Ok(()) => (
+                        format!("Promoted {} {} to {}", item.kind, item.name, to.name),
+                        Color::Green,
+                    ),
+                    Err(message) => (message, Color::Red),
+                }));
+
+                // show what's left to promote
+                shown.set(None);
+                load.set(DiffLoad::Loading);
+                load.set(compare(client, org_id, left, target).await);
+            }
+        }
+    });
+
+    let compare_target = props.compare_target;
+    let text_input = props.text_input;
+    let set_typing = move |typing: bool| {
+        if let Some(mut text_input) = text_input {
+            text_input.set(typing);
+        }
+    };
+    let target_is_production = right
+        .as_ref()
+        .is_some_and(|e| e.environment_flag == EnvironmentFlag::Production);
+    let target_name = right.as_ref().map(|e| e.name.clone()).unwrap_or_default();
+    hooks.use_terminal_events(move |event| match event {
+        TerminalEvent::Key(KeyEvent { code, kind, .. }) if kind != KeyEventKind::Release => {
+            let pending = confirming.read().clone();
+            match (code, pending) {
+                // production needs its name typed, like the CLI asks for,
+                // rather than a single key
+                (KeyCode::Char(c), Some(_)) if target_is_production => {
+                    let mut name = typed.read().clone();
+                    name.push(c);
+                    typed.set(name);
+                }
+                (KeyCode::Backspace, Some(_)) if target_is_production => {
+                    let mut name = typed.read().clone();
+                    name.pop();
+                    typed.set(name);
+                }
+                (KeyCode::Enter, Some(item)) if target_is_production => {
+                    confirming.set(None);
+                    set_typing(false);
+                    if typed.read().trim() == target_name {
+                        promote(item);
+                    } else {
+                        status.set(Some((
+                            format!("The name didn't match, {} wasn't promoted", item.name),
+                            Color::Yellow,
+                        )));
+                    }
+                }
+                (KeyCode::Char('y'), Some(item)) => {
+                    confirming.set(None);
+                    promote(item);
+                }
+                (KeyCode::Char('n') | KeyCode::Esc, Some(_)) => {
+                    confirming.set(None);
+                    set_typing(false);
+                }
+                (KeyCode::Char('p'), None) => match shown_item(&load.read(), shown.get()) {
+                    Some(_) if !is_local => status.set(Some((
+                        "Items can only be promoted to environments in the same organization"
+                            .into(),
+                        Color::Yellow,
+                    ))),
+                    Some(item) if is_promotable(&item) => {
+                        status.set(None);
+                        typed.set(String::new());
+                        set_typing(target_is_production);
+                        confirming.set(Some(item));
+                    }
+                    Some(_) => status.set(Some((
+                        "Only filters, config modifiers and translation sets in this \
+                        environment can be promoted"
+                            .into(),
+                        Color::Yellow,
+                    ))),
+                    None => {}
+                },
+                (KeyCode::Esc, None) => {
+                    if let Some(mut compare_target) = compare_target {
+                        compare_target.set(None);
+                    }
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    });
+
+    let left_name = left.map_or("none".into(), |e| e.name);
+
+    let diff_renderer: ItemRenderer<ItemDiff> = Box::new(|item, is_selected| {
+        let (color, background) = match is_selected {
+            true => (Color::Yellow, Color::DarkBlue),
+            false => (change_color(item.change), Color::Reset),
+        };
+        let marker = match item.change {
+            Change::Added => "+",
+            Change::Removed => "-",
+            Change::Changed => "~",
+        };
+
+        element! {
+            IoBox(width: 100pct, background_color: Some(background)) {
+                Text(content: format!("[{marker}] {}: {}", item.kind, item.name), color: Some(color))
+            }
+        }
+        .into_any()
+    });
+
+    let body = match load.read().clone() {
+        DiffLoad::Loading => element! {
+            Text(content: format!("Comparing {left_name} with {right_name}..."))
+        }
+        .into_any(),
+        DiffLoad::Failed(message) => element! {
+            Text(content: message, color: Color::Red)
+        }
+        .into_any(),
+        DiffLoad::Loaded(diffs) if diffs.is_empty() => element! {
+            Text(content: format!("No differences between {left_name} and {right_name}"))
+        }
+        .into_any(),
+        DiffLoad::Loaded(diffs) => {
+            let item = shown.get().and_then(|index| diffs.get(index)).cloned();
+            element! {
+                IoBox(width: 100pct, flex_direction: FlexDirection::Row) {
+                    IoBox(max_width: 50, flex_grow: 1.0) {
+                        ListBox<ItemDiff>(
+                            is_selected: true,
+                            title: format!("{} differences (enter to show)", diffs.len()),
+                            items: diffs,
+                            item_renderer: diff_renderer,
+                            on_select: move |index| shown.set(Some(index)),
+                        )
+                    }
+                    #(item.map(|item| {
+                        let changed: HashSet<String> =
+                            item.fields.iter().map(|f| f.path.clone()).collect();
+                        // each side's fields by path, with their values as JSON
+                        let [left_fields, right_fields] = [&item.left, &item.right].map(|side| {
+                            side.as_ref().map(|side| {
+                                flatten(side)
+                                    .into_iter()
+                                    .map(|(path, value)| (path, value.to_string()))
+                                    .collect::<Vec<_>>()
+                            })
+                        });
+                        element! {
+                            IoBox(flex_grow: 1.0, flex_direction: FlexDirection::Row) {
+                                IoBox(width: 50pct) {
+                                    ItemPane(
+                                        title: left_name.clone(),
+                                        fields: left_fields,
+                                        changed: changed.clone(),
+                                        color: Some(change_color(item.change)),
+                                    )
+                                }
+                                IoBox(width: 50pct) {
+                                    ItemPane(
+                                        title: right_name.clone(),
+                                        fields: right_fields,
+                                        changed: changed,
+                                        color: Some(change_color(item.change)),
+                                    )
+                                }
+                            }
+                        }
+                    }))
+                }
+            }
+            .into_any()
+        }
+    };
+
+    // the confirmation prompt takes the place of the last status
+    let prompt = match confirming.read().clone() {
+        Some(item) if target_is_production => Some((
+            format!(
+                "{right_name} is a production environment. Type its name and press enter to \
+                promote {} {} to it (esc to cancel): {}",
+                item.kind,
+                item.name,
+                *typed.read()
+            ),
+            Color::Red,
+        )),
+        Some(item) => Some((
+            format!("Promote {} {} to {right_name}? (y/n)", item.kind, item.name),
+            Color::Yellow,
+        )),
+        _ => status.read().clone(),
+    };
+
+    element! {
+        IoBox(width: 100pct, flex_direction: FlexDirection::Column) {
+            IoBox(width: 100pct, height: 1) {
+                Text(content: format!("{left_name} \u{2192} {right_name} (p to promote, esc to close)"))
+            }
+            IoBox(width: 100pct, height: 1, margin_bottom: 1) {
+                #(prompt.map(|(message, color)| element! {
+                    Text(content: message, color: Some(color))
+                }))
+            }
+            #(Some(body))
+        }
+    }
+}
+
+/// Fetch both environments, each through its own deployment's client, and
+/// compare them
+async fn compare(
+    client: Option<RedoxRequestClient>,
+    org_id: Option<i32>,
+    left: Option<Environment>,
+    target: Option<CompareTarget>,
+) -> DiffLoad {
+    let (Some(client), Some(org_id), Some(left), Some(target)) = (client, org_id, left, target)
+    else {
+        return DiffLoad::Failed("Pick an organization and environment first".into());
+    };
+    let result = async {
+        let left_items = EnvironmentItems::fetch(&client, org_id, &left.id, DEFAULT_KINDS).await?;
+        let right_items = EnvironmentItems::fetch(
+            &target.client,
+            target.org_id,
+            &target.environment.id,
+            DEFAULT_KINDS,
+        )
+        .await?;
+        Ok::<_, RedoxApiError>(diff(&left_items, &right_items))
+    }
+    .await;
+    match result {
+        Ok(diffs) => DiffLoad::Loaded(diffs),
+        Err(e) => DiffLoad::Failed(format!("Failed to compare environments. {e}")),
+    }
+}
+
+fn shown_item(load: &DiffLoad, shown: Option<usize>) -> Option<ItemDiff> {
+    match load {
+        DiffLoad::Loaded(diffs) => shown.and_then(|index| diffs.get(index)).cloned(),
+        _ => None,
+    }
+}
+
+/// Whether the item is one promotion copies, and the current environment has
+/// it to copy
+fn is_promotable(item: &ItemDiff) -> bool {
+    matches!(
+        item.kind,
+        EnvironmentResources::Filters
+            | EnvironmentResources::ConfigModifiers
+            | EnvironmentResources::TranslationSets
+    ) && item.change != Change::Added
+}
+
+/// Copy an item from one environment to the other, describing what went
+/// wrong if it couldn't be
+async fn promote_item(
+    client: &RedoxRequestClient,
+    org_id: i32,
+    from: &Environment,
+    to: &Environment,
+    item: &ItemDiff,
+) -> Result<(), String> {
+    let failed = |e: RedoxApiError| format!("Failed to promote {}. {e}", item.name);
+    let promotion = Promotion::fetch(client, org_id, &from.id, &to.id)
+        .await
+        .map_err(failed)?;
+
+    let mut items = PromotionItems::default();
+    match item.kind {
+        EnvironmentResources::Filters => items.filters.push(item.name.clone()),
+        EnvironmentResources::ConfigModifiers => items.config_modifiers.push(item.name.clone()),
+        _ => items.translation_sets.push(item.name.clone()),
+    }
+    let plan = promotion.plan(&items);
+    if !plan.missing_endpoints.is_empty() {
+        return Err(format!(
+            "{} has no sources or destinations named {}, create them first",
+            to.name,
+            plan.missing_endpoints.join(", ")
+        ));
+    }
+    // Only one item is promoted, so there's nothing partial to report
+    plan.apply(client, org_id, &to.id)
+        .await
+        .map_err(|e| failed(e.source))?;
+    Ok(())
+}
+
+/// Added items are green, removed ones red, and changes yellow, like most
+/// diff tools
+fn change_color(change: Change) -> Color {
+    match change {
+        Change::Added => Color::Green,
+        Change::Removed => Color::Red,
+        Change::Changed => Color::Yellow,
+    }
+}
+
+#[derive(Default, Props)]
+struct ItemPaneProps {
+    title: String,
+    /// The item's fields, or `None` if this side doesn't have it
+    fields: Option<Vec<(String, String)>>,
+    /// Paths of the fields that differ from the other side
+    changed: HashSet<String>,
+    /// What the differences are highlighted with
+    color: Option<Color>,
+}
+
+/// One side of an item, a field per line with the differences highlighted
+#[component]
+fn ItemPane(props: &ItemPaneProps) -> impl Into<AnyElement<'static>> {
+    let highlight = props.color.unwrap_or(Color::Reset);
+    let lines: Vec<(String, Color)> = match &props.fields {
+        Some(fields) => fields
+            .iter()
+            .map(|(path, value)| {
+                let color = match props.changed.contains(path) {
+                    true => highlight,
+                    // items on one side only are different all over
+                    false if props.changed.is_empty() => highlight,
+                    false => Color::Reset,
+                };
+                (format!("{path}: {value}"), color)
+            })
+            .collect(),
+        None => vec![(format!("Not in {}", props.title), Color::DarkGrey)],
+    };
+
+    element! {
+        BoxWithTitle(
+            title: props.title.clone(),
+            border_style: BorderStyle::Round,
+            border_color: Color::Reset,
+        ) {
+            IoBox(flex_direction: FlexDirection::Column, margin_left: 1, margin_right: 1, width: 100pct) {
+                #(lines.into_iter().map(|(line, color)| element! {
+                    Text(content: line, color: Some(color))
+                }))
+            }
+        }
+    }
+}
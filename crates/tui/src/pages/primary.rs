@@ -1,14 +1,15 @@
 use iocraft::{
-    hooks::{UseContext, UseState, UseTerminalEvents},
-    prelude::{component, element, AnyElement, Box as IoBox, Text},
+    hooks::{State, UseContext, UseState, UseTerminalEvents},
+    prelude::{component, element, AnyElement, Box as IoBox, Props, Text},
     Color, FlexDirection, Hooks, KeyCode, KeyEvent, KeyEventKind, TerminalEvent,
 };
-use redox_api::models::EnvironmentResources;
+use redox_api::models::{organization::Organization, EnvironmentResources};
 use strum::IntoEnumIterator;
 
 use crate::{
     app::AppContext,
-    shared_components::{ListBox, SingleItem},
+    pages::compare::{ComparePicker, CompareTarget},
+    shared_components::{ItemRenderer, ListBox, SingleItem},
 };
 
 #[derive(Copy, Clone, PartialEq)]
@@ -18,19 +19,27 @@ enum Selected {
     Organization,
     ResourcesList,
     Environment,
+    Compare,
+}
+
+#[derive(Default, Props)]
+pub struct PrimaryPageProps {
+    /// Set to the ID of the organization the user picks
+    pub requested_org: Option<State<Option<i32>>>,
+    /// Set to the environment the user picks to compare the current one with
+    pub compare_target: Option<State<Option<CompareTarget>>>,
 }
 
 #[component]
-pub fn PrimaryPage(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
+pub fn PrimaryPage(mut hooks: Hooks, props: &PrimaryPageProps) -> impl Into<AnyElement<'static>> {
     let cur_ctx = hooks.use_context::<AppContext>();
 
     let deployment_name = cur_ctx
         .current_deployment
         .clone()
         .map_or("none".into(), |d| d.name);
-    let current_org = cur_ctx
-        .current_organization
-        .map_or("none".into(), |d| d.to_string());
+    let current_org = cur_ctx.current_organization_name();
+    let organizations = cur_ctx.organizations.clone();
     let current_env = cur_ctx
         .env_ctx
         .clone()
@@ -54,6 +63,7 @@ pub fn PrimaryPage(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                     KeyCode::Char('o') => choose_selected(Selected::Organization),
                     KeyCode::Char('e') => choose_selected(Selected::Environment),
                     KeyCode::Char('r') => choose_selected(Selected::ResourcesList),
+                    KeyCode::Char('c') => choose_selected(Selected::Compare),
                     KeyCode::Enter => {}
                     _ => {}
                 }
@@ -62,7 +72,7 @@ pub fn PrimaryPage(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
         }
     });
 
-    let resource_list_renderer: Box<dyn FnMut(&EnvironmentResources, bool) -> AnyElement<'static>> =
+    let resource_list_renderer: ItemRenderer<EnvironmentResources> =
         Box::new(|item, is_selected| {
             let (color, background) = match is_selected {
                 true => (Color::Yellow, Color::DarkBlue),
@@ -77,6 +87,31 @@ pub fn PrimaryPage(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
             .into_any()
         });
 
+    let org_list_renderer: ItemRenderer<Organization> = Box::new(|org, is_selected| {
+        let (color, background) = match is_selected {
+            true => (Color::Yellow, Color::DarkBlue),
+            false => (Color::Reset, Color::Reset),
+        };
+
+        element! {
+            IoBox(width: 100pct, background_color: Some(background)) {
+                Text(content: format!("{} [{}]", org.name, org.id), color: Some(color))
+            }
+        }
+        .into_any()
+    });
+
+    let org_ids = organizations.iter().map(|o| o.id).collect::<Vec<i32>>();
+    let requested_org = props.requested_org;
+    let select_org = move |index: usize| {
+        if let (Some(mut requested_org), Some(org_id)) = (requested_org, org_ids.get(index)) {
+            requested_org.set(Some(*org_id));
+        }
+        cur_selected.set(Selected::None);
+    };
+
+    let compare_target = props.compare_target;
+
     element! {
         IoBox(
             width: 100pct,
@@ -114,6 +149,22 @@ pub fn PrimaryPage(mut hooks: Hooks) -> impl Into<AnyElement<'static>> {
                         item_renderer: resource_list_renderer,
                     )
                 }
+                #((cur_selected.get() == Selected::Organization).then(|| element! {
+                    IoBox(max_width: 60, flex_grow: 1.0) {
+                        ListBox<Organization>(
+                            is_selected: true,
+                            title: "Organizations (enter to switch)",
+                            items: organizations,
+                            item_renderer: org_list_renderer,
+                            on_select: select_org,
+                        )
+                    }
+                }))
+                #((cur_selected.get() == Selected::Compare).then(|| element! {
+                    IoBox(max_width: 60, flex_grow: 1.0) {
+                        ComparePicker(compare_target)
+                    }
+                }))
             }
         }
     }
@@ -1,7 +1,8 @@
 use iocraft::{
     hooks::{UseState, UseTerminalEvents},
     prelude::{
-        component, element, AnyElement, BorderStyle, Box as IoBox, Color, Props, Text, TextAlign,
+        component, element, AnyElement, BorderStyle, Box as IoBox, Color, Handler, Props, Text,
+        TextAlign,
     },
     FlexDirection, Hooks, KeyCode, KeyEvent, KeyEventKind, TerminalEvent,
 };
@@ -51,13 +52,18 @@ pub fn SingleItem(props: &mut SingleItemProps) -> impl Into<AnyElement<'static>>
     }
 }
 
+/// Renders a single item of a [ListBox], given whether it is the current selection
+pub type ItemRenderer<T> = Box<dyn FnMut(&T, bool) -> AnyElement<'static>>;
+
 #[derive(Props)]
 pub struct ListBoxProps<T> {
     pub is_selected: bool,
     pub title: String,
     pub items: Vec<T>,
-    pub item_renderer: Box<dyn FnMut(&T, bool) -> AnyElement<'static>>,
+    pub item_renderer: ItemRenderer<T>,
     pub selected_index: usize,
+    /// Called with the index of the current item when Enter is pressed
+    pub on_select: Handler<'static, usize>,
 }
 
 impl<T> Default for ListBoxProps<T> {
@@ -70,6 +76,7 @@ impl<T> Default for ListBoxProps<T> {
                 element! { Text(content: "not implemented") }.into_any()
             }),
             selected_index: 0,
+            on_select: Handler::default(),
         }
     }
 }
@@ -101,17 +108,23 @@ pub fn ListBox<T: 'static>(
     };
 
     let mut selection_down = move || {
-        if cur_selection.get() < items_length - 1 && is_selected.get() {
+        if cur_selection.get() + 1 < items_length && is_selected.get() {
             cur_selection.set(cur_selection.get() + 1)
         }
     };
 
+    // only the handler from the first render is kept, same as the closure below
+    let mut on_select = props.on_select.take();
+
     hooks.use_terminal_events({
         move |event| match event {
             TerminalEvent::Key(KeyEvent { code, kind, .. }) if kind != KeyEventKind::Release => {
                 match code {
                     KeyCode::Up => selection_up(),
                     KeyCode::Down => selection_down(),
+                    KeyCode::Enter if is_selected.get() && items_length > 0 => {
+                        on_select(cur_selection.get())
+                    }
                     _ => {}
                 }
             }
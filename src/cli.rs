@@ -0,0 +1,224 @@
+mod alerts;
+mod diff;
+mod environments;
+mod get;
+mod keys;
+mod logs;
+mod output;
+mod promote;
+mod snapshot;
+mod translation_sets;
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Parser, Subcommand};
+use redox_api::{
+    models::{
+        environment::{Environment, EnvironmentResource},
+        organization::OrganizationResource,
+        RequestType,
+    },
+    RedoxRequestClient,
+};
+use redox_core::{Configuration, Deployment, Selection};
+use std::{path::PathBuf, process::ExitCode};
+
+use alerts::AlertsCommand;
+use diff::DiffCommand;
+use environments::EnvCommand;
+use get::GetCommand;
+use keys::KeysCommand;
+use logs::LogsCommand;
+use promote::PromoteCommand;
+use snapshot::SnapshotCommand;
+use translation_sets::TranslationSetsCommand;
+
+/// TUI for interacting with the Redox platform control plane/API. Run without
+/// a subcommand to start the TUI.
+#[derive(Debug, Parser)]
+#[clap(author, version, about, name = "rc")]
+pub struct Args {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub subcommand: Option<CliCommand>,
+}
+
+/// Picks what to connect to, for the TUI and every command. Each flag falls
+/// back to its environment variable, then to the defaults in the config.
+#[derive(Debug, clap::Args)]
+pub struct GlobalArgs {
+    /// Configuration file to use instead of searching the current and
+    /// ancestor directories
+    #[clap(long, global = true, env = "RC_CONFIG")]
+    config: Option<PathBuf>,
+    /// Deployment to use instead of the default one
+    #[clap(long, global = true, env = "RC_DEPLOYMENT")]
+    deployment: Option<String>,
+    /// Organization ID to use instead of the deployment's default
+    #[clap(long, global = true, env = "RC_ORG")]
+    org: Option<i32>,
+    /// Environment ID or name
+    #[clap(long, global = true, env = "RC_ENV")]
+    env: Option<String>,
+}
+
+impl GlobalArgs {
+    pub fn selection(self) -> Selection {
+        Selection {
+            config: self.config,
+            deployment: self.deployment,
+            org: self.org,
+            env: self.env,
+        }
+    }
+}
+
+/// A non-interactive command, run in place of the TUI
+#[derive(Debug, Subcommand)]
+pub enum CliCommand {
+    /// Manage the keys used to authenticate with Redox
+    #[command(subcommand)]
+    Keys(KeysCommand),
+    /// Work with the environments in an organization
+    #[command(subcommand)]
+    Env(EnvCommand),
+    /// Search the logs of an environment
+    #[command(subcommand)]
+    Logs(LogsCommand),
+    /// Triage the alerts raised for an environment
+    #[command(subcommand)]
+    Alerts(AlertsCommand),
+    /// Fetch a single item by its ID
+    Get(GetCommand),
+    /// Import and export translation set code mappings as CSV
+    #[command(subcommand)]
+    TranslationSets(TranslationSetsCommand),
+    /// Back up an environment's configuration to files, or restore it
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Show how another environment's configuration differs from the
+    /// selected one's
+    Diff(DiffCommand),
+    /// Copy filters, config modifiers and translation sets to another
+    /// environment
+    Promote(PromoteCommand),
+}
+
+impl CliCommand {
+    /// Run the command, returning the exit code for the process
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::Keys(command) => command.execute(selection).await,
+            Self::Env(command) => command.execute(selection).await,
+            Self::Logs(command) => command.execute(selection).await,
+            Self::Alerts(command) => command.execute(selection).await,
+            Self::Get(command) => command.execute(selection).await,
+            Self::TranslationSets(command) => command.execute(selection).await,
+            Self::Snapshot(command) => command.execute(selection).await,
+            Self::Diff(command) => command.execute(selection).await,
+            Self::Promote(command) => command.execute(selection).await,
+        }
+    }
+}
+
+/// Load the configuration and pick the selected deployment from it. Returns
+/// the configuration file's path along with the deployment, for commands that
+/// write back to it.
+fn load_deployment(selection: &Selection) -> anyhow::Result<(PathBuf, Deployment)> {
+    let path = selection.config_path()?;
+    let configuration = Configuration::load(&path)?;
+    let deployment = selection
+        .deployment(&configuration)
+        .with_context(|| format!("Error loading {path:?}"))?
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "No default deployment in {path:?}, pick one with --deployment or RC_DEPLOYMENT"
+            )
+        })?;
+    Ok((path, deployment))
+}
+
+/// A client ready to make requests for an organization
+struct Session {
+    client: RedoxRequestClient,
+    org_id: i32,
+    env: Option<String>,
+}
+
+impl Session {
+    /// Build a client for the selected deployment and settle on an
+    /// organization. Without one selected or a default, the only organization
+    /// the credential can see is used.
+    async fn connect(selection: Selection) -> anyhow::Result<Self> {
+        let (_, deployment) = load_deployment(&selection)?;
+        let client = RedoxRequestClient::for_deployment(&deployment)?;
+
+        let org_id = match selection.org(&deployment) {
+            Some(org_id) => org_id,
+            None => {
+                let organizations = client
+                    .make_request(RequestType::List, OrganizationResource::new())
+                    .await?
+                    .into_list()
+                    .map(|list| list.organizations)
+                    .unwrap_or_default();
+                match organizations.as_slice() {
+                    [org] => org.id,
+                    _ => bail!(
+                        "Deployment {} has no default organization and can see {}, pick one \
+                        with --org or RC_ORG",
+                        deployment.name,
+                        organizations.len()
+                    ),
+                }
+            }
+        };
+
+        Ok(Self {
+            client,
+            org_id,
+            env: selection.env,
+        })
+    }
+
+    async fn environments(&self) -> anyhow::Result<Vec<Environment>> {
+        let environments = self
+            .client
+            .make_request(RequestType::List, EnvironmentResource::new(self.org_id))
+            .await?
+            .into_list()
+            .map(|list| list.environments)
+            .unwrap_or_default();
+        Ok(environments)
+    }
+
+    /// The selected environment, which may have been given by name so
+    /// scripts can use the names people actually remember
+    async fn environment(&self) -> anyhow::Result<Environment> {
+        let env = self
+            .env
+            .as_deref()
+            .ok_or_else(|| anyhow!("Pick an environment with --env or RC_ENV"))?;
+        self.find_environment(&self.environments().await?, env)
+    }
+
+    /// The environment with the given ID, or failing that, name
+    fn find_environment(
+        &self,
+        environments: &[Environment],
+        env: &str,
+    ) -> anyhow::Result<Environment> {
+        environments
+            .iter()
+            .find(|e| e.id == env)
+            .or_else(|| environments.iter().find(|e| e.matches(env)))
+            .cloned()
+            .ok_or_else(|| anyhow!("No environment {env} in organization {}", self.org_id))
+    }
+
+    /// The ID of the selected environment
+    async fn environment_id(&self) -> anyhow::Result<String> {
+        Ok(self.environment().await?.id)
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::anyhow;
+use clap::Subcommand;
+use redox_api::models::{
+    alert::{AlertResource, AlertStatus, AlertStatusBody},
+    RequestType,
+};
+use redox_core::Selection;
+use std::process::ExitCode;
+
+use super::{
+    output::{Listing, OutputArgs},
+    Session,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum AlertsCommand {
+    /// List the alerts raised for an environment
+    List {
+        #[command(flatten)]
+        output: OutputArgs,
+        /// Only alerts with this status, e.g. open
+        #[clap(long)]
+        status: Option<AlertStatus>,
+    },
+    /// Acknowledge an alert, so others can see someone is looking into it
+    #[command(name = "ack", alias = "acknowledge")]
+    Acknowledge {
+        /// ID of the alert
+        id: String,
+    },
+    /// Resolve an alert once whatever raised it has been dealt with
+    Resolve {
+        /// ID of the alert
+        id: String,
+    },
+}
+
+impl AlertsCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::List { output, status } => {
+                let session = Session::connect(selection).await?;
+                let environment_id = session.environment_id().await?;
+                let (response, payload) = session
+                    .client
+                    .make_request_with_payload(
+                        RequestType::List,
+                        AlertResource::new(session.org_id, &environment_id),
+                    )
+                    .await?;
+                let alerts = response
+                    .into_list()
+                    .map(|list| list.alerts)
+                    .unwrap_or_default();
+                let mut alerts = Listing::new(alerts, payload, "alerts");
+                // the alerts endpoint has no filters, so this one is ours
+                if let Some(status) = status {
+                    alerts.retain(|a| a.status == status);
+                }
+                output.print_listing(alerts)
+            }
+            Self::Acknowledge { id } => {
+                set_status(
+                    selection,
+                    id,
+                    AlertStatusBody::acknowledge(),
+                    "Acknowledged",
+                )
+                .await
+            }
+            Self::Resolve { id } => {
+                set_status(selection, id, AlertStatusBody::resolve(), "Resolved").await
+            }
+        }
+    }
+}
+
+/// Move an alert along to a new status
+async fn set_status(
+    selection: Selection,
+    id: String,
+    body: AlertStatusBody,
+    action: &str,
+) -> anyhow::Result<ExitCode> {
+    let session = Session::connect(selection).await?;
+    let environment_id = session.environment_id().await?;
+    let alert = session
+        .client
+        .make_request(
+            RequestType::Update(id.clone(), body),
+            AlertResource::new(session.org_id, &environment_id),
+        )
+        .await?
+        .into_single()
+        .ok_or_else(|| anyhow!("No alert {id}"))?;
+    eprintln!("{action} alert {} ({})", alert.id, alert.name);
+    Ok(ExitCode::SUCCESS)
+}
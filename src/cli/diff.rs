@@ -0,0 +1,242 @@
+use clap::{Args, ValueEnum};
+use redox_api::{
+    diff::{diff, Change, EnvironmentItems, ItemDiff, DEFAULT_KINDS},
+    models::EnvironmentResources,
+};
+use redox_core::Selection;
+use serde::Serialize;
+use serde_json::Value;
+use std::process::ExitCode;
+
+use super::{output::OutputArgs, Session};
+
+/// Everything that can be compared
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DiffKind {
+    Source,
+    Destination,
+    Filter,
+    ConfigModifier,
+    TranslationSet,
+    AuthCredential,
+    Alert,
+    Log,
+}
+
+impl From<DiffKind> for EnvironmentResources {
+    fn from(kind: DiffKind) -> Self {
+        match kind {
+            DiffKind::Source => Self::Sources,
+            DiffKind::Destination => Self::Destination,
+            DiffKind::Filter => Self::Filters,
+            DiffKind::ConfigModifier => Self::ConfigModifiers,
+            DiffKind::TranslationSet => Self::TranslationSets,
+            DiffKind::AuthCredential => Self::AuthCredential,
+            DiffKind::Alert => Self::Alerts,
+            DiffKind::Log => Self::Logs,
+        }
+    }
+}
+
+/// Compare the selected environment with another one, which can be in a
+/// different organization or deployment. Items are matched by name, and IDs
+/// and timestamps are ignored. Exits with 1 if there are differences.
+#[derive(Debug, Args)]
+pub struct DiffCommand {
+    /// ID or name of the environment to compare with
+    #[clap(long)]
+    other_env: String,
+    /// Organization of the other environment, if it's not the selected one
+    #[clap(long)]
+    other_org: Option<i32>,
+    /// Deployment of the other environment, if it's not the selected one.
+    /// Without --other-org, the deployment's default organization is used.
+    #[clap(long)]
+    other_deployment: Option<String>,
+    /// Compare these kinds of items instead of the configuration, which is
+    /// every kind but logs and alerts. Those are left out on purpose: they're
+    /// matched by ID, which never matches between environments, so they only
+    /// show up as added or removed. Ask for them to compare them anyway, in
+    /// which case only the last day of logs is fetched.
+    #[clap(long, value_enum, value_delimiter = ',')]
+    kind: Vec<DiffKind>,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// One line of the output: a changed field, or a whole item that's only on
+/// one side
+#[derive(Debug, Serialize)]
+struct DiffRow {
+    kind: String,
+    name: String,
+    change: Change,
+    field: Option<String>,
+    left: Option<Value>,
+    right: Option<Value>,
+}
+
+impl DiffCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let kinds: Vec<EnvironmentResources> = if self.kind.is_empty() {
+            DEFAULT_KINDS.to_vec()
+        } else {
+            self.kind.iter().map(|kind| (*kind).into()).collect()
+        };
+
+        let other_selection = Selection {
+            org: self.other_org.or(match self.other_deployment {
+                // A different deployment has different organizations
+                Some(_) => None,
+                None => selection.org,
+            }),
+            deployment: self.other_deployment.or(selection.deployment.clone()),
+            env: Some(self.other_env),
+            config: selection.config.clone(),
+        };
+
+        let left = Session::connect(selection).await?;
+        let left_env = left.environment().await?;
+        let right = Session::connect(other_selection).await?;
+        let right_env = right.environment().await?;
+        eprintln!(
+            "Comparing {} (organization {}) with {} (organization {})",
+            left_env.name, left.org_id, right_env.name, right.org_id
+        );
+
+        let left_items =
+            EnvironmentItems::fetch(&left.client, left.org_id, &left_env.id, &kinds).await?;
+        let right_items =
+            EnvironmentItems::fetch(&right.client, right.org_id, &right_env.id, &kinds).await?;
+        let (rows, exit_code) = report(&diff(&left_items, &right_items));
+
+        if rows.is_empty() {
+            eprintln!("No differences");
+        } else {
+            self.output.print(&rows)?;
+        }
+        Ok(exit_code)
+    }
+}
+
+/// The rows to print and the code to exit with. Like diff(1), finding
+/// differences isn't an error but still exits with 1.
+fn report(diffs: &[ItemDiff]) -> (Vec<DiffRow>, ExitCode) {
+    let rows: Vec<DiffRow> = diffs.iter().flat_map(rows).collect();
+    let exit_code = match diffs.is_empty() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    };
+    (rows, exit_code)
+}
+
+fn rows(item: &ItemDiff) -> Vec<DiffRow> {
+    let row = |field, left, right| DiffRow {
+        kind: item.kind.to_string(),
+        name: item.name.clone(),
+        change: item.change,
+        field,
+        left,
+        right,
+    };
+    match item.change {
+        Change::Added | Change::Removed => vec![row(None, None, None)],
+        Change::Changed => item
+            .fields
+            .iter()
+            .map(|field| {
+                row(
+                    Some(field.path.clone()),
+                    field.left.clone(),
+                    field.right.clone(),
+                )
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redox_api::diff::FieldDiff;
+    use serde_json::json;
+
+    use super::*;
+
+    fn item(name: &str, change: Change, fields: Vec<FieldDiff>) -> ItemDiff {
+        ItemDiff {
+            kind: EnvironmentResources::Filters,
+            name: name.into(),
+            change,
+            fields,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn field(path: &str, left: Option<Value>, right: Option<Value>) -> FieldDiff {
+        FieldDiff {
+            path: path.into(),
+            left,
+            right,
+        }
+    }
+
+    #[test]
+    fn one_row_per_changed_field_or_whole_item() {
+        let diffs = [
+            item("Only right", Change::Added, Vec::new()),
+            item("Only left", Change::Removed, Vec::new()),
+            item(
+                "Edited",
+                Change::Changed,
+                vec![
+                    field("type", Some(json!("allow")), Some(json!("deny"))),
+                    field("dataModel", Some(json!("Scheduling")), None),
+                ],
+            ),
+        ];
+
+        let (rows, exit_code) = report(&diffs);
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.name.as_str(),
+                    r.change,
+                    r.field.as_deref(),
+                    r.left.clone(),
+                    r.right.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("Only right", Change::Added, None, None, None),
+                ("Only left", Change::Removed, None, None, None),
+                (
+                    "Edited",
+                    Change::Changed,
+                    Some("type"),
+                    Some(json!("allow")),
+                    Some(json!("deny"))
+                ),
+                (
+                    "Edited",
+                    Change::Changed,
+                    Some("dataModel"),
+                    Some(json!("Scheduling")),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(exit_code, ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn exits_successfully_without_differences() {
+        let (rows, exit_code) = report(&[]);
+        assert!(rows.is_empty());
+        assert_eq!(exit_code, ExitCode::SUCCESS);
+    }
+}
@@ -0,0 +1,40 @@
+use clap::Subcommand;
+use redox_api::models::{environment::EnvironmentResource, RequestType};
+use redox_core::Selection;
+use std::process::ExitCode;
+
+use super::{
+    output::{Listing, OutputArgs},
+    Session,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum EnvCommand {
+    /// List the environments in the organization
+    List {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+impl EnvCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::List { output } => {
+                let session = Session::connect(selection).await?;
+                let (response, payload) = session
+                    .client
+                    .make_request_with_payload(
+                        RequestType::List,
+                        EnvironmentResource::new(session.org_id),
+                    )
+                    .await?;
+                let environments = response
+                    .into_list()
+                    .map(|list| list.environments)
+                    .unwrap_or_default();
+                output.print_listing(Listing::new(environments, payload, "environments"))
+            }
+        }
+    }
+}
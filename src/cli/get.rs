@@ -0,0 +1,132 @@
+use anyhow::anyhow;
+use clap::{Args, ValueEnum};
+use redox_api::{
+    models::{
+        alert::AlertResource, auth_credential::AuthCredentialResource,
+        config_modifier::ConfigModifierResource, destination::DestinationResource,
+        environment::EnvironmentResource, filter::FilterResource, log::LogResource,
+        organization::OrganizationResource, source::SourceResource,
+        translation_set::TranslationSetResource, RedoxApiResource, RequestType,
+    },
+    RedoxRequestClient,
+};
+use redox_core::Selection;
+use serde::Serialize;
+use std::{fmt::Debug, process::ExitCode};
+
+use super::{output::OutputArgs, Session};
+
+/// Everything that can be fetched by ID
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ResourceKind {
+    Organization,
+    Environment,
+    Log,
+    Alert,
+    Source,
+    Destination,
+    Filter,
+    ConfigModifier,
+    TranslationSet,
+    AuthCredential,
+}
+
+#[derive(Debug, Args)]
+pub struct GetCommand {
+    /// The kind of item to fetch
+    resource: ResourceKind,
+    /// ID of the item
+    id: String,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+impl GetCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let session = Session::connect(selection).await?;
+        let client = &session.client;
+        let org_id = session.org_id;
+        let id = self.id;
+        let output = &self.output;
+
+        match self.resource {
+            ResourceKind::Organization => {
+                return get(client, output, OrganizationResource::new(), id).await;
+            }
+            ResourceKind::Environment => {
+                return get(client, output, EnvironmentResource::new(org_id), id).await;
+            }
+            _ => {}
+        }
+
+        let env_id = session.environment_id().await?;
+        match self.resource {
+            ResourceKind::Log => get(client, output, LogResource::new(org_id, &env_id), id).await,
+            ResourceKind::Alert => {
+                get(client, output, AlertResource::new(org_id, &env_id), id).await
+            }
+            ResourceKind::Source => {
+                get(client, output, SourceResource::new(org_id, &env_id), id).await
+            }
+            ResourceKind::Destination => {
+                get(
+                    client,
+                    output,
+                    DestinationResource::new(org_id, &env_id),
+                    id,
+                )
+                .await
+            }
+            ResourceKind::Filter => {
+                get(client, output, FilterResource::new(org_id, &env_id), id).await
+            }
+            ResourceKind::ConfigModifier => {
+                get(
+                    client,
+                    output,
+                    ConfigModifierResource::new(org_id, &env_id),
+                    id,
+                )
+                .await
+            }
+            ResourceKind::TranslationSet => {
+                get(
+                    client,
+                    output,
+                    TranslationSetResource::new(org_id, &env_id),
+                    id,
+                )
+                .await
+            }
+            ResourceKind::AuthCredential => {
+                get(
+                    client,
+                    output,
+                    AuthCredentialResource::new(org_id, &env_id),
+                    id,
+                )
+                .await
+            }
+            ResourceKind::Organization | ResourceKind::Environment => unreachable!(),
+        }
+    }
+}
+
+async fn get<R>(
+    client: &RedoxRequestClient,
+    output: &OutputArgs,
+    resource: R,
+    id: String,
+) -> anyhow::Result<ExitCode>
+where
+    R: RedoxApiResource + Debug,
+    R::Item: Serialize,
+{
+    let (response, payload) = client
+        .make_request_with_payload(RequestType::Get(id.clone()), resource)
+        .await?;
+    let item = response
+        .into_single()
+        .ok_or_else(|| anyhow!("Nothing returned for {id}"))?;
+    output.print_response(&item, &payload)
+}
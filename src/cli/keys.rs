@@ -0,0 +1,306 @@
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Subcommand};
+use redox_api::{
+    key::{generate_key, parse_algorithm, Jwks, Key},
+    models::{
+        auth_credential::{AuthCredential, AuthCredentialBody, AuthCredentialResource},
+        RequestType,
+    },
+    RedoxRequestClient,
+};
+use redox_core::{util::write_private_file, Configuration, Deployment, DeploymentAuth, Selection};
+use std::{path::PathBuf, process::ExitCode};
+use tracing::info;
+
+use super::{load_deployment, Session};
+
+const DEFAULT_API_HOST: &str = "https://api.redoxengine.com";
+
+#[derive(Debug, Subcommand)]
+pub enum KeysCommand {
+    /// Generate a key pair for a Redox API credential. The private key is
+    /// written to a file and the public JWKS, to upload to Redox, is printed.
+    Generate(GenerateCommand),
+    /// Replace a deployment's key: generate a new key, add it to the Redox
+    /// credential, check a token can be minted with it, then point the
+    /// deployment at it
+    Rotate(RotateCommand),
+}
+
+impl KeysCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::Generate(command) => command.execute(selection).await,
+            Self::Rotate(command) => command.execute(selection).await,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateCommand {
+    /// Signing algorithm for the key, e.g. RS384 or ES256
+    #[clap(long, default_value = "RS384")]
+    algorithm: String,
+    /// Size of the key in bits, for RSA algorithms only. At least 2048.
+    #[clap(long)]
+    bits: Option<usize>,
+    /// Where to write the private key. Defaults to `<kid>.pem` in the current
+    /// directory.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Overwrite the private key file if it already exists
+    #[clap(long)]
+    force: bool,
+    /// Append a deployment using the new key to the configuration file, with
+    /// the given name
+    #[clap(long, requires = "client_id")]
+    add_deployment: Option<String>,
+    /// Client ID of the Redox API credential, for the new deployment
+    #[clap(long)]
+    client_id: Option<String>,
+    /// API host for the new deployment
+    #[clap(long, default_value = DEFAULT_API_HOST)]
+    api_host: String,
+    /// Auth host for the new deployment, if it differs from the API host
+    #[clap(long)]
+    auth_host: Option<String>,
+}
+
+impl GenerateCommand {
+    async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let algorithm = parse_algorithm(Some(&self.algorithm))?;
+        // a new deployment can start a new configuration file
+        let config_path = selection
+            .config_path()
+            .unwrap_or_else(|_| PathBuf::from("rc.yml"));
+
+        // Catch a clashing deployment before writing a key nobody will use
+        if let Some(name) = &self.add_deployment {
+            if config_path.exists() {
+                let configuration = Configuration::load(&config_path)?;
+                if configuration.deployments.iter().any(|d| &d.name == name) {
+                    bail!("A deployment named {name} already exists in {config_path:?}");
+                }
+            }
+        }
+
+        let key = generate_key(algorithm, self.bits)?;
+
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("{}.pem", key.kid())));
+        write_private_file(&output, &key.private_key_pem, self.force)
+            .context("Error writing private key")?;
+        eprintln!("Wrote private key to {}", output.display());
+
+        let jwks = Jwks {
+            keys: vec![key.jwk.clone()],
+        };
+        println!("{}", serde_json::to_string_pretty(&jwks)?);
+
+        if let (Some(name), Some(client_id)) = (self.add_deployment, self.client_id) {
+            let private_key_file = output
+                .canonicalize()
+                .unwrap_or(output)
+                .to_string_lossy()
+                .to_string();
+            let deployment = Deployment {
+                name,
+                auth_host: self.auth_host,
+                api_host: self.api_host,
+                auth: DeploymentAuth {
+                    kid: key.kid().to_string(),
+                    client_id,
+                    private_key_file: Some(private_key_file),
+                    algorithm: Some(format!("{:?}", algorithm)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            Configuration::append_deployment(&config_path, &deployment)?;
+            info!("Added deployment {} to {:?}", deployment.name, config_path);
+            eprintln!(
+                "Added deployment {} to {}",
+                deployment.name,
+                config_path.display()
+            );
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Rotates the key of the selected deployment, for the credential in the
+/// selected environment
+#[derive(Debug, Args)]
+pub struct RotateCommand {
+    /// ID of the credential. Defaults to the credential with the deployment's
+    /// client ID.
+    #[clap(long)]
+    credential: Option<String>,
+    /// Signing algorithm for the new key. Defaults to the deployment's current
+    /// algorithm.
+    #[clap(long)]
+    algorithm: Option<String>,
+    /// Size of the new key in bits, for RSA algorithms only. At least 2048.
+    #[clap(long)]
+    bits: Option<usize>,
+    /// Where to write the new private key. Defaults to `<kid>.pem` in the
+    /// current directory.
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+    /// Remove the old key from the credential once the new one works. By
+    /// default both are kept, so anything still using the old key keeps
+    /// working until it's removed by hand.
+    #[clap(long)]
+    remove_old: bool,
+}
+
+impl RotateCommand {
+    async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let (config_path, deployment) = load_deployment(&selection)?;
+        let session = Session::connect(selection).await?;
+        let algorithm = parse_algorithm(
+            self.algorithm
+                .as_deref()
+                .or(deployment.auth.algorithm.as_deref()),
+        )?;
+
+        let client = session.client.clone();
+        let resource =
+            AuthCredentialResource::new(session.org_id, &session.environment_id().await?);
+        let credential =
+            find_credential(&client, &resource, &deployment, self.credential.as_deref()).await?;
+        if credential.jwks_url.is_some() {
+            bail!(
+                "Credential {} reads its keys from {}, rotate the key there instead",
+                credential.id,
+                credential.jwks_url.unwrap_or_default()
+            );
+        }
+        let old_kid = deployment.auth.kid.clone();
+        let credential_id = credential.id.clone();
+        eprintln!(
+            "Rotating key {old_kid} of credential {} ({credential_id})",
+            credential.name
+        );
+
+        // 1. Write the new key first, so it's never registered without us
+        // holding the private half
+        let key = generate_key(algorithm, self.bits)?;
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("{}.pem", key.kid())));
+        write_private_file(&output, &key.private_key_pem, false)
+            .context("Error writing private key")?;
+        eprintln!("Wrote new private key to {}", output.display());
+
+        // 2. Register the public half alongside the current key
+        let mut body = AuthCredentialBody::from(credential);
+        body.add_key(key.jwk.clone());
+        client
+            .make_request(
+                RequestType::Update(credential_id.clone(), body.clone()),
+                resource.clone(),
+            )
+            .await
+            .context("Error uploading the new public key")?;
+        eprintln!("Added key {} to the credential", key.kid());
+
+        // 3. Make sure Redox accepts it before anything depends on it. This
+        // client doesn't use the token cache, which would hand back a token
+        // minted with the old key.
+        let new_client = RedoxRequestClient::new(
+            &deployment.api_host,
+            deployment.auth_host.as_deref(),
+            Key::from_pem(
+                &key.private_key_pem,
+                Some(&format!("{:?}", algorithm)),
+                None,
+            )?,
+            key.kid(),
+            &deployment.auth.client_id,
+        )?;
+        if let Err(error) = new_client.refresh_jwt().await {
+            eprintln!("Redox rejected the new key, removing it from the credential");
+            body.remove_key(key.kid());
+            client
+                .make_request(RequestType::Update(credential_id, body), resource)
+                .await
+                .context("Error removing the rejected key, remove it by hand")?;
+            return Err(error).context("Error getting a token with the new key");
+        }
+        eprintln!("Got a token with the new key");
+
+        // 4. Point the deployment at the new key. Any other key source is
+        // replaced, since the new key only exists in the file.
+        let private_key_file = output
+            .canonicalize()
+            .unwrap_or(output)
+            .to_string_lossy()
+            .to_string();
+        let auth = DeploymentAuth {
+            kid: key.kid().to_string(),
+            client_id: deployment.auth.client_id.clone(),
+            private_key_file: Some(private_key_file),
+            algorithm: Some(format!("{:?}", algorithm)),
+            ..Default::default()
+        };
+        Configuration::update_deployment_auth(&config_path, &deployment.name, &auth)?;
+        eprintln!(
+            "Updated deployment {} in {}",
+            deployment.name,
+            config_path.display()
+        );
+
+        // 5. Retire the old key, now that nothing in our config uses it
+        if self.remove_old {
+            body.remove_key(&old_kid);
+            new_client
+                .make_request(RequestType::Update(credential_id, body), resource)
+                .await
+                .context("Error removing the old key, remove it by hand")?;
+            eprintln!("Removed old key {old_kid} from the credential");
+        } else {
+            eprintln!(
+                "Old key {old_kid} is still registered, remove it from the credential \
+                once nothing uses it"
+            );
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Get the credential by ID, or else look for the one the deployment
+/// authenticates with
+async fn find_credential(
+    client: &RedoxRequestClient,
+    resource: &AuthCredentialResource,
+    deployment: &Deployment,
+    id: Option<&str>,
+) -> anyhow::Result<AuthCredential> {
+    if let Some(id) = id {
+        return client
+            .make_request(RequestType::Get(id.to_string()), resource.clone())
+            .await?
+            .into_single()
+            .ok_or_else(|| anyhow!("No credential returned for {id}"));
+    }
+
+    let credentials = client
+        .make_request(RequestType::List, resource.clone())
+        .await?
+        .into_list()
+        .map(|list| list.auth_credentials)
+        .unwrap_or_default();
+    credentials
+        .into_iter()
+        .find(|c| c.client_id == deployment.auth.client_id)
+        .ok_or_else(|| {
+            anyhow!(
+                "No credential with client ID {} in this environment, pass one with --credential",
+                deployment.auth.client_id
+            )
+        })
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use redox_api::models::{
+    log::{LogFilter, LogResource, LogStatus},
+    RequestType,
+};
+use redox_core::Selection;
+use std::{cmp::Reverse, process::ExitCode};
+
+use super::{
+    output::{Listing, OutputArgs},
+    Session,
+};
+
+#[derive(Debug, Subcommand)]
+pub enum LogsCommand {
+    /// List the logs of an environment, newest first. The filters are applied
+    /// by Redox, so narrowing them down keeps large environments fast.
+    List(ListLogsCommand),
+}
+
+impl LogsCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::List(command) => command.execute(selection).await,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ListLogsCommand {
+    #[command(flatten)]
+    output: OutputArgs,
+    /// Only logs created at or after this RFC 3339 time
+    #[clap(long)]
+    start: Option<DateTime<Utc>>,
+    /// Only logs created before this RFC 3339 time
+    #[clap(long)]
+    end: Option<DateTime<Utc>>,
+    /// Only logs with this status, e.g. failed
+    #[clap(long)]
+    status: Option<LogStatus>,
+    /// Only logs from this source ID
+    #[clap(long)]
+    source: Option<String>,
+    /// Only logs to this destination ID
+    #[clap(long)]
+    destination: Option<String>,
+    /// Only logs for this data model
+    #[clap(long)]
+    data_model: Option<String>,
+    /// Only logs for this event type
+    #[clap(long)]
+    event_type: Option<String>,
+}
+
+impl ListLogsCommand {
+    async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let session = Session::connect(selection).await?;
+        let environment_id = session.environment_id().await?;
+        let filter = LogFilter {
+            start: self.start,
+            end: self.end,
+            status: self.status,
+            source_id: self.source,
+            destination_id: self.destination,
+            data_model: self.data_model,
+            event_type: self.event_type,
+        };
+
+        let (response, payload) = session
+            .client
+            .make_request_with_payload(
+                RequestType::List,
+                LogResource::new(session.org_id, &environment_id).with_filter(filter),
+            )
+            .await?;
+        let logs = response
+            .into_list()
+            .map(|list| list.logs)
+            .unwrap_or_default();
+        let mut logs = Listing::new(logs, payload, "logs");
+        // Redox doesn't promise an order, and pages can interleave
+        logs.sort_by_key(|log| Reverse(log.created_at));
+        self.output.print_listing(logs)
+    }
+}
@@ -0,0 +1,358 @@
+mod query;
+
+use anyhow::Context;
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use std::{io::Write, iter, process::ExitCode};
+
+use query::Query;
+
+/// Widest a table cell gets before it's cut short
+const MAX_CELL_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal
+    #[default]
+    Table,
+    Json,
+    Yaml,
+    Csv,
+    /// One compact JSON document per line, one line per item
+    Ndjson,
+}
+
+/// How a command prints its result
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    /// Format to print the result in
+    #[clap(long, value_enum, default_value_t)]
+    output: OutputFormat,
+    /// Only print the parts of the result selected by a JSONPath-style query,
+    /// e.g. `$.environments[*].id` or `$..name`. For anything fetched from
+    /// Redox, the query runs on the response's payload as the API sent it,
+    /// so it sees every field, including ones rc doesn't know about.
+    #[clap(long, value_parser = Query::parse)]
+    query: Option<Query>,
+}
+
+impl OutputArgs {
+    /// Print a result rc worked out itself, such as a diff, to stdout in the
+    /// requested format. The query runs on the result as `--output json`
+    /// would print it.
+    pub fn print<T: Serialize>(&self, result: &T) -> anyhow::Result<ExitCode> {
+        let value = serde_json::to_value(result)?;
+        let value = match &self.query {
+            Some(query) => query.select(&value),
+            None => value,
+        };
+        self.write(&value)
+    }
+
+    /// Print a result fetched from the API. Without a query, the model is
+    /// printed, so tables get rc's columns. With one, it runs on the payload
+    /// the model was parsed from.
+    pub fn print_response<T: Serialize>(
+        &self,
+        result: &T,
+        payload: &Value,
+    ) -> anyhow::Result<ExitCode> {
+        self.write(&self.select(result, payload)?)
+    }
+
+    /// Print a list fetched from the API, after any filtering or sorting
+    pub fn print_listing<T: Serialize>(&self, listing: Listing<T>) -> anyhow::Result<ExitCode> {
+        let (items, payload) = listing.into_parts();
+        self.print_response(&items, &payload)
+    }
+
+    fn select<T: Serialize>(&self, result: &T, payload: &Value) -> anyhow::Result<Value> {
+        Ok(match &self.query {
+            Some(query) => query.select(payload),
+            None => serde_json::to_value(result)?,
+        })
+    }
+
+    fn write(&self, value: &Value) -> anyhow::Result<ExitCode> {
+        let rendered = match self.output {
+            OutputFormat::Json => serde_json::to_string_pretty(value)? + "\n",
+            OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            OutputFormat::Ndjson => render_ndjson(value)?,
+            OutputFormat::Csv => render_csv(value)?,
+            OutputFormat::Table => render_table(value),
+        };
+        std::io::stdout()
+            .write_all(rendered.as_bytes())
+            .context("Error writing output")?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// A list fetched from the API, with each model paired with its item in the
+/// payload's list, so filtering or sorting the models keeps what `--query`
+/// sees in step
+pub struct Listing<T> {
+    items: Vec<(T, Value)>,
+    payload: Value,
+    /// The payload field holding the list, e.g. `alerts`
+    field: &'static str,
+}
+
+impl<T> Listing<T> {
+    /// Pair the models with the items of the payload's `field` list they
+    /// were parsed from
+    pub fn new(models: Vec<T>, mut payload: Value, field: &'static str) -> Self {
+        let raw = match payload.get_mut(field).map(Value::take) {
+            Some(Value::Array(raw)) => raw,
+            _ => Vec::new(),
+        };
+        let items = models
+            .into_iter()
+            .zip(raw.into_iter().chain(iter::repeat(Value::Null)))
+            .collect();
+        Self {
+            items,
+            payload,
+            field,
+        }
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.items.retain(|(model, _)| keep(model));
+    }
+
+    pub fn sort_by_key<K: Ord>(&mut self, mut key: impl FnMut(&T) -> K) {
+        self.items.sort_by_key(|(model, _)| key(model));
+    }
+
+    /// The models, and the payload with its list matching them
+    fn into_parts(self) -> (Vec<T>, Value) {
+        let (models, raw): (Vec<T>, Vec<Value>) = self.items.into_iter().unzip();
+        let mut payload = self.payload;
+        if let Value::Object(fields) = &mut payload {
+            fields.insert(self.field.to_string(), Value::Array(raw));
+        }
+        (models, payload)
+    }
+}
+
+fn render_ndjson(value: &Value) -> anyhow::Result<String> {
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    };
+    let mut rendered = String::new();
+    for item in items {
+        rendered += &serde_json::to_string(item)?;
+        rendered.push('\n');
+    }
+    Ok(rendered)
+}
+
+fn render_csv(value: &Value) -> anyhow::Result<String> {
+    let (columns, rows) = to_rows(value);
+    if columns.is_empty() {
+        return Ok(String::new());
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Lists become one row per item, a single item becomes field/value rows
+/// and a bare scalar is printed as is
+fn render_table(value: &Value) -> String {
+    let (columns, rows) = match value {
+        Value::Array(items) if items.is_empty() => return String::new(),
+        Value::Array(_) => to_rows(value),
+        Value::Object(_) => {
+            let mut fields = Vec::new();
+            flatten(value, "", &mut fields);
+            let rows = fields.into_iter().map(|(k, v)| vec![k, v]).collect();
+            (vec!["FIELD".to_string(), "VALUE".to_string()], rows)
+        }
+        value => return cell(value) + "\n",
+    };
+
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|row| row.into_iter().map(truncate).collect())
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([column.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut rendered = String::new();
+    let header = columns.iter().map(|c| c.to_uppercase()).collect();
+    for row in [header].into_iter().chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        rendered += line.trim_end();
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// Turn a result into a header and rows, one row per item of a list.
+/// Nested objects become dotted columns such as `source.name`, and the
+/// columns are every field seen, in the order they were first seen.
+fn to_rows(value: &Value) -> (Vec<String>, Vec<Vec<String>>) {
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    };
+
+    let flattened: Vec<Vec<(String, String)>> = items
+        .into_iter()
+        .map(|item| {
+            let mut fields = Vec::new();
+            match item {
+                Value::Object(_) => flatten(item, "", &mut fields),
+                scalar => fields.push(("value".to_string(), cell(scalar))),
+            }
+            fields
+        })
+        .collect();
+
+    let mut columns: Vec<String> = Vec::new();
+    for (name, _) in flattened.iter().flatten() {
+        if !columns.contains(name) {
+            columns.push(name.clone());
+        }
+    }
+    let rows = flattened
+        .into_iter()
+        .map(|fields| {
+            columns
+                .iter()
+                .map(|column| {
+                    fields
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(_, value)| value.clone())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+    (columns, rows)
+}
+
+fn flatten(value: &Value, prefix: &str, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let name = match prefix {
+                    "" => key.clone(),
+                    prefix => format!("{prefix}.{key}"),
+                };
+                flatten(value, &name, fields);
+            }
+        }
+        value => fields.push((prefix.to_string(), cell(value))),
+    }
+}
+
+/// Text for a single value. Strings lose their quotes, null is empty and
+/// anything nested is left as compact JSON.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.is_empty() => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn truncate(cell: String) -> String {
+    let cell = cell.replace(['\n', '\r'], " ");
+    if cell.chars().count() <= MAX_CELL_WIDTH {
+        return cell;
+    }
+    let mut short: String = cell.chars().take(MAX_CELL_WIDTH - 1).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::cmp::Reverse;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Alert {
+        id: String,
+        status: String,
+    }
+
+    fn output(query: Option<&str>) -> OutputArgs {
+        OutputArgs {
+            output: OutputFormat::Json,
+            query: query.map(|q| Query::parse(q).unwrap()),
+        }
+    }
+
+    fn alert(id: &str, status: &str) -> Alert {
+        Alert {
+            id: id.into(),
+            status: status.into(),
+        }
+    }
+
+    #[test]
+    fn queries_fields_the_model_does_not_have() {
+        let payload = json!({ "id": "alert-1", "status": "open", "severity": "high" });
+        let model = alert("alert-1", "open");
+
+        let selected = output(Some("$.severity")).select(&model, &payload).unwrap();
+        assert_eq!(selected, json!("high"));
+
+        // without a query the model is printed as is
+        let selected = output(None).select(&model, &payload).unwrap();
+        assert_eq!(selected, json!({ "id": "alert-1", "status": "open" }));
+    }
+
+    #[test]
+    fn listing_keeps_the_payload_in_step() {
+        let payload = json!({
+            "alerts": [
+                { "id": "alert-1", "status": "open", "severity": "low" },
+                { "id": "alert-2", "status": "resolved", "severity": "high" },
+                { "id": "alert-3", "status": "open", "severity": "high" },
+            ],
+        });
+        let models = vec![
+            alert("alert-1", "open"),
+            alert("alert-2", "resolved"),
+            alert("alert-3", "open"),
+        ];
+
+        let mut listing = Listing::new(models, payload, "alerts");
+        listing.retain(|a| a.status == "open");
+        listing.sort_by_key(|a| Reverse(a.id.clone()));
+        let (models, payload) = listing.into_parts();
+
+        let ids: Vec<_> = models.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["alert-3", "alert-1"]);
+        let query = Query::parse("$.alerts[*].severity").unwrap();
+        assert_eq!(query.select(&payload), json!(["high", "low"]));
+    }
+}
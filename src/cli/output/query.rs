@@ -0,0 +1,213 @@
+use anyhow::{anyhow, bail};
+use serde_json::Value;
+
+/// A parsed JSONPath-style query. Supports the subset that covers pulling
+/// fields out of API results: `$`, `.name`, `['name']`, `[0]`, `[-1]`,
+/// `[*]`, `.*` and `..name` for recursive descent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(i64),
+    Wildcard,
+    /// Every field with the given name at any depth below the current value
+    Descendant(String),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> anyhow::Result<Self> {
+        let query = query.trim();
+        let mut rest = query.strip_prefix('$').unwrap_or(query);
+        // allow `name.other` as shorthand for `$.name.other`
+        let shorthand;
+        if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+            shorthand = format!(".{rest}");
+            rest = &shorthand;
+        }
+
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                let (name, tail) = split_name(after);
+                if name.is_empty() {
+                    bail!("Expected a field name after `..` in query {query}");
+                }
+                segments.push(Segment::Descendant(name.to_string()));
+                rest = tail;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let (name, tail) = split_name(after);
+                segments.push(match name {
+                    "" => bail!("Expected a field name after `.` in query {query}"),
+                    "*" => Segment::Wildcard,
+                    name => Segment::Field(name.to_string()),
+                });
+                rest = tail;
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after
+                    .find(']')
+                    .ok_or_else(|| anyhow!("Unclosed `[` in query {query}"))?;
+                segments.push(parse_bracket(after[..end].trim(), query)?);
+                rest = &after[end + 1..];
+            } else {
+                bail!("Unexpected `{rest}` in query {query}");
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Whether the query can only ever select a single value
+    fn is_definite(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|s| matches!(s, Segment::Field(_) | Segment::Index(_)))
+    }
+
+    /// Run the query. A query without wildcards or descent gives the value
+    /// it points at, or null if there's nothing there. Any other query gives
+    /// an array of every match.
+    pub fn select(&self, value: &Value) -> Value {
+        let mut current = vec![value];
+        for segment in &self.segments {
+            current = current
+                .into_iter()
+                .flat_map(|value| segment.apply(value))
+                .collect();
+        }
+
+        if self.is_definite() {
+            current.first().map_or(Value::Null, |v| (*v).clone())
+        } else {
+            Value::Array(current.into_iter().cloned().collect())
+        }
+    }
+}
+
+impl Segment {
+    fn apply<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match (self, value) {
+            (Self::Field(name), Value::Object(map)) => map.get(name).into_iter().collect(),
+            (Self::Index(index), Value::Array(items)) => {
+                let index = if *index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    *index
+                };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .into_iter()
+                    .collect()
+            }
+            (Self::Wildcard, Value::Array(items)) => items.iter().collect(),
+            (Self::Wildcard, Value::Object(map)) => map.values().collect(),
+            (Self::Descendant(name), _) => {
+                let mut found = Vec::new();
+                collect_descendants(value, name, &mut found);
+                found
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn collect_descendants<'a>(value: &'a Value, name: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(v) = map.get(name) {
+                found.push(v);
+            }
+            map.values()
+                .for_each(|v| collect_descendants(v, name, found));
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|v| collect_descendants(v, name, found)),
+        _ => {}
+    }
+}
+
+/// Split a dotted field name off the front of the rest of the query
+fn split_name(query: &str) -> (&str, &str) {
+    let end = query.find(['.', '[', ']']).unwrap_or(query.len());
+    query.split_at(end)
+}
+
+fn parse_bracket(inner: &str, query: &str) -> anyhow::Result<Segment> {
+    if inner == "*" {
+        return Ok(Segment::Wildcard);
+    }
+    if let Some(name) = inner
+        .strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+    {
+        return Ok(Segment::Field(name.to_string()));
+    }
+    inner.parse().map(Segment::Index).map_err(|_| {
+        anyhow!("Expected an index, `*` or a quoted name in `[{inner}]` in query {query}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn select(query: &str, value: &Value) -> Value {
+        Query::parse(query).unwrap().select(value)
+    }
+
+    #[test]
+    fn parses_shorthand_like_the_full_form() {
+        assert_eq!(Query::parse("a.b").unwrap(), Query::parse("$.a.b").unwrap());
+        assert_eq!(Query::parse(" $ ").unwrap().segments, vec![]);
+    }
+
+    #[test]
+    fn selects_fields_and_indexes() {
+        let value = json!({"items": [{"name": "a"}, {"name": "b"}, {"name": "c"}]});
+        assert_eq!(select("$.items[0].name", &value), json!("a"));
+        assert_eq!(select("$['items'][1][\"name\"]", &value), json!("b"));
+        assert_eq!(select("$.items[-1].name", &value), json!("c"));
+        assert_eq!(select("$", &value), value);
+    }
+
+    #[test]
+    fn definite_queries_give_null_when_nothing_matches() {
+        let value = json!({"items": [1]});
+        assert_eq!(select("$.missing", &value), Value::Null);
+        assert_eq!(select("$.items[5]", &value), Value::Null);
+        assert_eq!(select("$.items[-2]", &value), Value::Null);
+    }
+
+    #[test]
+    fn wildcards_give_every_match() {
+        let value = json!([{"id": 1}, {"id": 2}]);
+        assert_eq!(select("$[*].id", &value), json!([1, 2]));
+        assert_eq!(select("$.*.id", &value), json!([1, 2]));
+        assert_eq!(select("$[0].*", &value), json!([1]));
+        assert_eq!(select("$[*].missing", &value), json!([]));
+    }
+
+    #[test]
+    fn descent_finds_fields_at_any_depth() {
+        let value = json!({
+            "name": "top",
+            "children": [{"name": "a", "child": {"name": "b"}}],
+        });
+        assert_eq!(select("$..name", &value), json!(["top", "a", "b"]));
+        assert_eq!(select("$.children..name", &value), json!(["a", "b"]));
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in ["$.", "$..", "$[0", "$[x]", "$.a]", "$.a[]"] {
+            assert!(Query::parse(query).is_err(), "{query} should be rejected");
+        }
+    }
+}
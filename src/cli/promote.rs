@@ -0,0 +1,242 @@
+use anyhow::{anyhow, bail, Context};
+use clap::Args;
+use redox_api::{
+    models::environment::{Environment, EnvironmentFlag},
+    promote::{PromotedItem, Promotion, PromotionItems},
+    snapshot::{ConfigItem, RestoreAction},
+};
+use redox_core::Selection;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    process::ExitCode,
+};
+
+use super::{output::OutputArgs, Session};
+
+/// Copy filters, config modifiers and translation sets from the selected
+/// environment to another one in the same organization. Items are matched by
+/// name: ones the target has are updated, the rest are created, and the
+/// sources and destinations they're attached to are swapped for the target's
+/// endpoints of the same name. The changes are shown before they're made,
+/// and promoting to a production environment has to be confirmed.
+#[derive(Debug, Args)]
+pub struct PromoteCommand {
+    /// ID or name of the environment to promote to. Defaults to the one
+    /// environment a step up from the selected one: development to staging,
+    /// or staging to production.
+    #[clap(long)]
+    to: Option<String>,
+    /// Name of a filter to promote. Can be given more than once.
+    #[clap(long = "filter")]
+    filters: Vec<String>,
+    /// Name of a config modifier to promote. Can be given more than once.
+    #[clap(long = "config-modifier")]
+    config_modifiers: Vec<String>,
+    /// Name of a translation set to promote. Can be given more than once.
+    #[clap(long = "translation-set")]
+    translation_sets: Vec<String>,
+    /// Promote every filter, config modifier and translation set
+    #[clap(long, conflicts_with_all = ["filters", "config_modifiers", "translation_sets"])]
+    all: bool,
+    /// Only show what would change
+    #[clap(long)]
+    dry_run: bool,
+    /// Promote to a production environment without asking first
+    #[clap(long)]
+    yes: bool,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// One line of the preview: a field that changes, or an item that's created
+#[derive(Debug, Serialize)]
+struct PromotionRow {
+    kind: String,
+    name: String,
+    action: RestoreAction,
+    field: Option<String>,
+    current: Option<Value>,
+    promoted: Option<Value>,
+}
+
+impl PromoteCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let session = Session::connect(selection).await?;
+        let source = session.environment().await?;
+        let environments = session.environments().await?;
+        let target = match &self.to {
+            Some(to) => session.find_environment(&environments, to)?,
+            None => next_environment(&source, &environments)?,
+        };
+        if target.id == source.id {
+            bail!("Can't promote {} to itself", source.name);
+        }
+
+        let promotion =
+            Promotion::fetch(&session.client, session.org_id, &source.id, &target.id).await?;
+        let items = self.items(&promotion, &source)?;
+        let plan = promotion.plan(&items);
+
+        eprintln!(
+            "Promoting from {} ({:?}) to {} ({:?})",
+            source.name, source.environment_flag, target.name, target.environment_flag
+        );
+        for (kind, name) in &plan.unchanged {
+            eprintln!("{kind} {name} is already up to date in {}", target.name);
+        }
+        if plan.is_empty() {
+            eprintln!("Nothing to promote");
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let rows: Vec<PromotionRow> = plan.changes.iter().flat_map(rows).collect();
+        self.output.print(&rows)?;
+
+        if !plan.missing_endpoints.is_empty() {
+            bail!(
+                "{} has no sources or destinations named {}. Create them before promoting \
+                items attached to them.",
+                target.name,
+                plan.missing_endpoints.join(", ")
+            );
+        }
+        if self.dry_run {
+            eprintln!("Dry run, nothing was changed in {}", target.name);
+            return Ok(ExitCode::SUCCESS);
+        }
+        if target.environment_flag == EnvironmentFlag::Production && !self.yes {
+            confirm_production(&target, plan.changes.len())?;
+        }
+
+        let promoted = match plan
+            .apply(&session.client, session.org_id, &target.id)
+            .await
+        {
+            Ok(promoted) => promoted,
+            Err(error) => {
+                for item in &error.restored {
+                    eprintln!("Promoted {} {} before the failure", item.kind, item.name);
+                }
+                return Err(error.into());
+            }
+        };
+        eprintln!("Promoted {} items to {}", promoted.len(), target.name);
+        Ok(ExitCode::SUCCESS)
+    }
+
+    /// The items picked on the command line, checking they all exist
+    fn items(&self, promotion: &Promotion, source: &Environment) -> anyhow::Result<PromotionItems> {
+        let snapshot = promotion.source();
+        if self.all {
+            return Ok(PromotionItems {
+                filters: names(&snapshot.filters),
+                config_modifiers: names(&snapshot.config_modifiers),
+                translation_sets: names(&snapshot.translation_sets),
+            });
+        }
+
+        let items = PromotionItems {
+            filters: self.filters.clone(),
+            config_modifiers: self.config_modifiers.clone(),
+            translation_sets: self.translation_sets.clone(),
+        };
+        if items.is_empty() {
+            bail!(
+                "Pick what to promote with --filter, --config-modifier or --translation-set, or \
+                pass --all"
+            );
+        }
+
+        let mut unknown = Vec::new();
+        unknown.extend(missing(&items.filters, &snapshot.filters));
+        unknown.extend(missing(&items.config_modifiers, &snapshot.config_modifiers));
+        unknown.extend(missing(&items.translation_sets, &snapshot.translation_sets));
+        if !unknown.is_empty() {
+            bail!("{} has no {}", source.name, unknown.join(", "));
+        }
+        Ok(items)
+    }
+}
+
+/// The only environment of the kind the source promotes to
+fn next_environment(
+    source: &Environment,
+    environments: &[Environment],
+) -> anyhow::Result<Environment> {
+    let flag = source.environment_flag.promotes_to().ok_or_else(|| {
+        anyhow!(
+            "{} is a production environment, pick where to promote it to with --to",
+            source.name
+        )
+    })?;
+    let candidates: Vec<&Environment> = environments
+        .iter()
+        .filter(|e| e.environment_flag == flag)
+        .collect();
+    match candidates.as_slice() {
+        [target] => Ok((*target).clone()),
+        _ => bail!(
+            "There are {} {flag:?} environments, pick one to promote to with --to",
+            candidates.len()
+        ),
+    }
+}
+
+/// Make the user type the production environment's name before changing it.
+/// Without a terminal to ask on, --yes is the only way through.
+fn confirm_production(target: &Environment, count: usize) -> anyhow::Result<()> {
+    if !io::stdin().is_terminal() {
+        bail!(
+            "{} is a production environment, pass --yes to promote to it",
+            target.name
+        );
+    }
+    eprint!(
+        "{} is a production environment. Type its name to promote {count} items to it: ",
+        target.name
+    );
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("Error reading confirmation")?;
+    if answer.trim() != target.name {
+        bail!("Promotion cancelled, nothing was changed");
+    }
+    Ok(())
+}
+
+fn names<T: ConfigItem>(items: &[T]) -> Vec<String> {
+    items.iter().map(|i| i.name().to_string()).collect()
+}
+
+/// The names that aren't any of the items', described for an error
+fn missing<T: ConfigItem>(names: &[String], items: &[T]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|name| !items.iter().any(|i| i.name() == name.as_str()))
+        .map(|name| format!("{} {name}", T::KIND))
+        .collect()
+}
+
+fn rows(item: &PromotedItem) -> Vec<PromotionRow> {
+    let row = |field, current, promoted| PromotionRow {
+        kind: item.kind.to_string(),
+        name: item.name.clone(),
+        action: item.action,
+        field,
+        current,
+        promoted,
+    };
+    match item.action {
+        RestoreAction::Create => vec![row(None, None, None)],
+        RestoreAction::Update => item
+            .fields
+            .iter()
+            .map(|f| row(Some(f.path.clone()), f.left.clone(), f.right.clone()))
+            .collect(),
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use clap::{Subcommand, ValueEnum};
+use redox_api::{
+    models::environment::Environment,
+    snapshot::{ConfigItem, EnvironmentSnapshot},
+};
+use redox_core::{
+    util::{create_private_dir, parse_yaml, write_private_file},
+    Selection,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use super::{output::OutputArgs, Session};
+
+/// Describes where a snapshot came from. Restoring doesn't need it, it's for
+/// whoever finds the directory later.
+const MANIFEST: &str = "environment";
+
+const SOURCES: &str = "sources";
+const DESTINATIONS: &str = "destinations";
+const FILTERS: &str = "filters";
+const CONFIG_MODIFIERS: &str = "config-modifiers";
+const TRANSLATION_SETS: &str = "translation-sets";
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum SnapshotFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl SnapshotFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    fn render<T: Serialize>(self, value: &T) -> anyhow::Result<String> {
+        Ok(match self {
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Json => serde_json::to_string_pretty(value)? + "\n",
+        })
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotCommand {
+    /// Write the sources, destinations (including their subscriptions),
+    /// filters, config modifiers and translation sets of the selected
+    /// environment to a directory, one file per item. Anything secret the API
+    /// returns, like verification tokens, ends up in the files too.
+    Export {
+        /// Directory to write to
+        dir: PathBuf,
+        #[clap(long, value_enum, default_value_t)]
+        format: SnapshotFormat,
+        /// Replace an earlier snapshot in the directory
+        #[clap(long)]
+        force: bool,
+    },
+    /// Recreate the configuration in a snapshot directory in the selected
+    /// environment. Items with the same name as existing ones update them,
+    /// the rest are created, and nothing is deleted.
+    Restore {
+        /// Directory written by `snapshot export`
+        dir: PathBuf,
+        /// Only show what would be created and updated
+        #[clap(long)]
+        dry_run: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+/// The snapshot's [MANIFEST] file
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    environment: Environment,
+    #[serde(rename = "exportedAt")]
+    exported_at: DateTime<Utc>,
+}
+
+impl SnapshotCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        match self {
+            Self::Export { dir, format, force } => {
+                let session = Session::connect(selection).await?;
+                let environment = session.environment().await?;
+                let snapshot =
+                    EnvironmentSnapshot::fetch(&session.client, session.org_id, &environment.id)
+                        .await?;
+                let manifest = Manifest {
+                    environment,
+                    exported_at: Utc::now(),
+                };
+                write_snapshot(&dir, format, force, &manifest, &snapshot)?;
+                eprintln!(
+                    "Exported {} items from {} to {}",
+                    snapshot.len(),
+                    manifest.environment.name,
+                    dir.display()
+                );
+                Ok(ExitCode::SUCCESS)
+            }
+            Self::Restore {
+                dir,
+                dry_run,
+                output,
+            } => {
+                let snapshot = read_snapshot(&dir)?;
+                let session = Session::connect(selection).await?;
+                let environment = session.environment().await?;
+                let restored = match snapshot
+                    .restore(&session.client, session.org_id, &environment.id, dry_run)
+                    .await
+                {
+                    Ok(restored) => restored,
+                    Err(error) => {
+                        // Say what has to be cleaned up or retried
+                        if !error.restored.is_empty() {
+                            eprintln!("Restored before the failure:");
+                            output.print(&error.restored)?;
+                        }
+                        return Err(error.into());
+                    }
+                };
+                if dry_run {
+                    eprintln!(
+                        "Dry run, nothing was changed in {}. This is what a restore would do:",
+                        environment.name
+                    );
+                }
+                output.print(&restored)
+            }
+        }
+    }
+}
+
+fn write_snapshot(
+    dir: &Path,
+    format: SnapshotFormat,
+    force: bool,
+    manifest: &Manifest,
+    snapshot: &EnvironmentSnapshot,
+) -> anyhow::Result<()> {
+    if dir
+        .read_dir()
+        .is_ok_and(|mut entries| entries.next().is_some())
+    {
+        if !force {
+            bail!(
+                "{} is not empty, pass --force to replace the snapshot in it",
+                dir.display()
+            );
+        }
+        // Items deleted since the last export mustn't linger in the snapshot
+        for kind in [
+            SOURCES,
+            DESTINATIONS,
+            FILTERS,
+            CONFIG_MODIFIERS,
+            TRANSLATION_SETS,
+        ] {
+            let kind_dir = dir.join(kind);
+            if kind_dir.exists() {
+                fs::remove_dir_all(&kind_dir)
+                    .with_context(|| format!("Error removing {kind_dir:?}"))?;
+            }
+        }
+    }
+
+    create_private_dir(dir)?;
+    let path = dir.join(MANIFEST).with_extension(format.extension());
+    write_private_file(&path, &format.render(manifest)?, true)?;
+
+    write_items(&dir.join(SOURCES), format, &snapshot.sources)?;
+    write_items(&dir.join(DESTINATIONS), format, &snapshot.destinations)?;
+    write_items(&dir.join(FILTERS), format, &snapshot.filters)?;
+    write_items(
+        &dir.join(CONFIG_MODIFIERS),
+        format,
+        &snapshot.config_modifiers,
+    )?;
+    write_items(
+        &dir.join(TRANSLATION_SETS),
+        format,
+        &snapshot.translation_sets,
+    )?;
+    Ok(())
+}
+
+/// Write each item to a file named after it, so the snapshot reads well in a
+/// diff or code review
+fn write_items<T>(dir: &Path, format: SnapshotFormat, items: &[T]) -> anyhow::Result<()>
+where
+    T: ConfigItem + Serialize,
+{
+    if items.is_empty() {
+        return Ok(());
+    }
+    create_private_dir(dir)?;
+
+    let mut used = HashSet::new();
+    for item in items {
+        let mut name = file_name(item.name());
+        // Names aren't unique, IDs are
+        if !used.insert(name.clone()) {
+            name = format!("{name}-{}", file_name(item.id()));
+            used.insert(name.clone());
+        }
+        let path = dir.join(name).with_extension(format.extension());
+        write_private_file(&path, &format.render(item)?, true)?;
+    }
+    Ok(())
+}
+
+fn read_snapshot(dir: &Path) -> anyhow::Result<EnvironmentSnapshot> {
+    if !dir.is_dir() {
+        bail!("No snapshot directory at {}", dir.display());
+    }
+    let snapshot = EnvironmentSnapshot {
+        sources: read_items(&dir.join(SOURCES))?,
+        destinations: read_items(&dir.join(DESTINATIONS))?,
+        filters: read_items(&dir.join(FILTERS))?,
+        config_modifiers: read_items(&dir.join(CONFIG_MODIFIERS))?,
+        translation_sets: read_items(&dir.join(TRANSLATION_SETS))?,
+    };
+    if snapshot.is_empty() {
+        bail!("The snapshot in {} has no items", dir.display());
+    }
+    Ok(snapshot)
+}
+
+/// Read every YAML or JSON file in the directory, in name order. A missing
+/// directory just means there are no items of that kind.
+fn read_items<T: DeserializeOwned>(dir: &Path) -> anyhow::Result<Vec<T>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Error reading {dir:?}"))? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|e| e == "yaml" || e == "yml" || e == "json")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            // YAML is a superset of JSON, so one parser handles both
+            let file = File::open(path).with_context(|| format!("Error opening {path:?}"))?;
+            parse_yaml(file).with_context(|| format!("Error parsing {path:?}"))
+        })
+        .collect()
+}
+
+/// A file name for an item, keeping to characters that are safe everywhere
+fn file_name(name: &str) -> String {
+    let mut file_name = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            file_name.push(c.to_ascii_lowercase());
+        } else if !file_name.ends_with('-') {
+            file_name.push('-');
+        }
+    }
+    let file_name = file_name.trim_matches('-');
+    if file_name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        file_name.to_string()
+    }
+}
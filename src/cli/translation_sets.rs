@@ -0,0 +1,125 @@
+use anyhow::{anyhow, bail, Context};
+use clap::Subcommand;
+use redox_api::models::{
+    translation_set::{
+        mappings_from_csv, mappings_to_csv, TranslationSet, TranslationSetBody,
+        TranslationSetResource,
+    },
+    RequestType,
+};
+use redox_core::Selection;
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use super::Session;
+
+#[derive(Debug, Subcommand)]
+pub enum TranslationSetsCommand {
+    /// Replace a translation set's code mappings with the ones in a CSV file.
+    /// The CSV needs a header row of sourceCode, sourceDisplay, targetCode and
+    /// targetDisplay, and the display columns can be left out.
+    Import {
+        /// ID or name of the translation set
+        set: String,
+        /// CSV file to read the code mappings from
+        file: PathBuf,
+        /// Create a set with this name if there isn't one with that ID or
+        /// name, instead of failing
+        #[clap(long)]
+        create: bool,
+    },
+    /// Write a translation set's code mappings as CSV, in the format import
+    /// reads
+    Export {
+        /// ID or name of the translation set
+        set: String,
+        /// File to write to instead of stdout
+        #[clap(long, short)]
+        file: Option<PathBuf>,
+    },
+}
+
+impl TranslationSetsCommand {
+    pub async fn execute(self, selection: Selection) -> anyhow::Result<ExitCode> {
+        let session = Session::connect(selection).await?;
+        let resource =
+            TranslationSetResource::new(session.org_id, &session.environment_id().await?);
+        let sets = session
+            .client
+            .make_request(RequestType::List, resource.clone())
+            .await?
+            .into_list()
+            .map(|list| list.translation_sets)
+            .unwrap_or_default();
+
+        match self {
+            Self::Import { set, file, create } => {
+                let reader =
+                    File::open(&file).with_context(|| format!("Error opening {file:?}"))?;
+                let mappings =
+                    mappings_from_csv(reader).with_context(|| format!("Error reading {file:?}"))?;
+                let count = mappings.len();
+
+                let (request, action) = match find(&sets, &set) {
+                    Some(existing) => {
+                        let body = TranslationSetBody {
+                            mappings,
+                            ..existing.clone().into()
+                        };
+                        (RequestType::Update(existing.id.clone(), body), "Updated")
+                    }
+                    None if !create => bail!(
+                        "No translation set {set}, pass --create to create one with that name"
+                    ),
+                    None => {
+                        let body = TranslationSetBody {
+                            name: set.clone(),
+                            description: None,
+                            source_code_system: None,
+                            target_code_system: None,
+                            mappings,
+                        };
+                        (RequestType::Create(body), "Created")
+                    }
+                };
+                session.client.make_request(request, resource).await?;
+                eprintln!("{action} translation set {set} with {count} code mappings");
+                Ok(ExitCode::SUCCESS)
+            }
+            Self::Export { set, file } => {
+                let id = find(&sets, &set)
+                    .map(|s| s.id.clone())
+                    .ok_or_else(|| anyhow!("No translation set {set}"))?;
+                // The list leaves out mappings, so fetch the whole set
+                let full = session
+                    .client
+                    .make_request(RequestType::Get(id), resource)
+                    .await?
+                    .into_single()
+                    .ok_or_else(|| anyhow!("No translation set {set}"))?;
+
+                match file {
+                    Some(path) => {
+                        let writer = File::create(&path)
+                            .with_context(|| format!("Error creating {path:?}"))?;
+                        mappings_to_csv(BufWriter::new(writer), &full.mappings)?;
+                        eprintln!("Wrote {} code mappings to {path:?}", full.mappings.len());
+                    }
+                    None => mappings_to_csv(io::stdout().lock(), &full.mappings)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+        }
+    }
+}
+
+/// The set with the given ID, or failing that, name
+fn find<'a>(sets: &'a [TranslationSet], set: &str) -> Option<&'a TranslationSet> {
+    sets.iter()
+        .find(|s| s.id == set)
+        .or_else(|| sets.iter().find(|s| s.name == set))
+}
@@ -1,7 +1,11 @@
 #![forbid(unsafe_code)]
 #![deny(clippy::all)]
 
+mod cli;
+
 use anyhow::Context;
+use clap::Parser;
+use cli::Args;
 use redox_core::util::{paths, ResultTraced};
 use redox_tui::Tui;
 use std::{
@@ -16,10 +20,21 @@ use tracing_subscriber::{
 
 #[tokio::main]
 async fn main() -> anyhow::Result<ExitCode> {
-    initialize_tracing(false);
-    info!("Starting Redox Commander");
-    Tui::start().await;
-    Ok(ExitCode::SUCCESS)
+    let args = Args::parse();
+    let selection = args.global.selection();
+
+    match args.subcommand {
+        None => {
+            initialize_tracing(false);
+            info!("Starting Redox Commander");
+            Tui::start(selection).await;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(subcommand) => {
+            initialize_tracing(true);
+            subcommand.execute(selection).await
+        }
+    }
 }
 
 /// Set up tracing to a log file, and optionally the console as well. If there's
@@ -87,7 +102,7 @@ fn initialize_log_file() -> anyhow::Result<File> {
     let path = paths::log_file();
     paths::create_parent(&path)?;
 
-    if fs::metadata(&path).map_or(false, |metadata| metadata.len() > MAX_FILE_SIZE) {
+    if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_FILE_SIZE) {
         // Rename new->old, overwriting old. If that fails, just delete new so
         // it doesn't grow indefinitely. Failure shouldn't stop us from logging
         // though